@@ -48,9 +48,12 @@ impl Thread {
         PID.with(|x| x.set(pid));
     }
 
+    /// Get tid and pid of current task, or `(0, 0)` outside of any task.
     #[export_name = "hal_thread_get_tid"]
     pub fn get_tid() -> (u64, u64) {
-        (TID.with(|x| x.get()), PID.with(|x| x.get()))
+        let tid = TID.try_with(|x| x.get()).unwrap_or(0);
+        let pid = PID.try_with(|x| x.get()).unwrap_or(0);
+        (tid, pid)
     }
 }
 
@@ -67,6 +70,22 @@ pub fn timer_now() -> Duration {
         .unwrap()
 }
 
+/// Sleep until `deadline`.
+#[export_name = "hal_sleep_until"]
+pub fn sleep_until(deadline: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+    let duration = deadline.checked_sub(timer_now()).unwrap_or_default();
+    Box::pin(async_std::task::sleep(duration))
+}
+
+/// Set a new timer. After `deadline`, the `callback` will be called with the current time.
+#[export_name = "hal_timer_set"]
+pub fn timer_set(deadline: Duration, callback: Box<dyn FnOnce(Duration) + Send + 'static>) {
+    async_std::task::spawn(async move {
+        sleep_until(deadline).await;
+        callback(timer_now());
+    });
+}
+
 /// Initialize the HAL.
 ///
 /// This function must be called at the beginning.
@@ -48,6 +48,20 @@ pub fn timer_now() -> Duration {
     unimplemented!()
 }
 
+/// Sleep until `deadline`.
+#[linkage = "weak"]
+#[export_name = "hal_sleep_until"]
+pub fn sleep_until(_deadline: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+    unimplemented!()
+}
+
+/// Set a new timer. After `deadline`, the `callback` will be called with the current time.
+#[linkage = "weak"]
+#[export_name = "hal_timer_set"]
+pub fn timer_set(_deadline: Duration, _callback: Box<dyn FnOnce(Duration) + Send + 'static>) {
+    unimplemented!()
+}
+
 #[repr(C)]
 pub struct PhysFrame {
     paddr: PhysAddr,
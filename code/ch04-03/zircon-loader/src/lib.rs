@@ -8,7 +8,7 @@ extern crate alloc;
 extern crate log;
 
 use {
-    alloc::{boxed::Box, sync::Arc, vec::Vec},
+    alloc::{boxed::Box, sync::Arc},
     core::{future::Future, pin::Pin},
     kernel_hal::MMUFlags,
     xmas_elf::ElfFile,
@@ -17,7 +17,10 @@ use {
 };
 
 mod kcounter;
+pub mod processargs;
 
+// The bootstrap message of userboot is not in processargs format:
+// its handles are identified by their positions.
 // These describe userboot itself
 const K_PROC_SELF: usize = 0;
 const K_VMARROOT_SELF: usize = 1;
@@ -79,6 +82,7 @@ pub fn run_userboot(images: &Images<impl AsRef<[u8]>>, cmdline: &str) -> Arc<Pro
             )
             .unwrap();
         vmar.map_from_elf(&elf, vdso_vmo.clone()).unwrap();
+        proc.set_vdso_base(vmar.addr());
         let offset = elf
             .get_symbol_address("zcore_syscall_entry")
             .expect("failed to locate syscall entry") as usize;
@@ -152,7 +156,7 @@ pub fn run_userboot(images: &Images<impl AsRef<[u8]>>, cmdline: &str) -> Arc<Pro
         Handle::new(instrumentation_data_vmo, Rights::DEFAULT_VMO);
 
     // check: handle to root proc should be only
-    let data = Vec::from(cmdline.replace(':', "\0") + "\0");
+    let data = processargs::encode_strings(cmdline.split(':'));
     let msg = MessagePacket { data, handles };
     kernel_channel.write(msg).unwrap();
 
@@ -16,6 +16,10 @@ struct Opt {
     prebuilt_path: PathBuf,
     #[structopt(default_value = "")]
     cmdline: String,
+    /// Dump live kernel objects at exit
+    #[cfg(feature = "audit")]
+    #[structopt(long)]
+    dump_objects: bool,
 }
 
 #[async_std::main]
@@ -28,6 +32,30 @@ async fn main() {
     drop(images);
     let proc = proc.downcast_arc::<Process>().unwrap();
     proc.wait_for_end().await;
+    #[cfg(feature = "audit")]
+    if opt.dump_objects {
+        dump_objects();
+    }
+}
+
+#[cfg(feature = "audit")]
+fn dump_objects() {
+    use zircon_object::object::audit::*;
+    println!("live kernel objects:");
+    for (type_name, count) in live_object_counts() {
+        println!("  {:<16} {}", type_name, count);
+    }
+    for object in live_objects() {
+        println!(
+            "  [{:?}] koid={} type={} name={:?} creator={} at {}",
+            object.created_at,
+            object.koid,
+            object.type_name,
+            object.name,
+            object.creator,
+            object.location
+        );
+    }
 }
 
 fn open_images(path: &Path) -> std::io::Result<Images<Vec<u8>>> {
@@ -0,0 +1,339 @@
+//! Processargs: the bootstrap message of a new process.
+//!
+//! The message starts with a header, followed by an array of handle info
+//! tags, one for each handle in the message, and NUL-terminated strings of
+//! args, environ and names. See `zircon/processargs.h`.
+
+use {
+    alloc::{string::String, vec::Vec},
+    core::mem::size_of,
+    zircon_object::{ipc::MessagePacket, object::Handle, ZxError, ZxResult},
+};
+
+/// The protocol magic of a processargs message.
+pub const PA_PROTOCOL: u32 = 0x4150_585d;
+/// The version of processargs protocol.
+pub const PA_VERSION: u32 = 0x0001_0000;
+
+// Handle types of the current process.
+pub const PA_PROC_SELF: u8 = 0x01;
+pub const PA_THREAD_SELF: u8 = 0x02;
+pub const PA_JOB_DEFAULT: u8 = 0x03;
+pub const PA_VMAR_ROOT: u8 = 0x04;
+pub const PA_VMAR_LOADED: u8 = 0x05;
+// Handle types of the program loader.
+pub const PA_LDSVC_LOADER: u8 = 0x10;
+pub const PA_VMO_VDSO: u8 = 0x11;
+pub const PA_VMO_STACK: u8 = 0x13;
+pub const PA_VMO_EXECUTABLE: u8 = 0x14;
+pub const PA_VMO_BOOTDATA: u8 = 0x1A;
+pub const PA_VMO_BOOTFS: u8 = 0x1B;
+pub const PA_VMO_KERNEL_FILE: u8 = 0x17;
+// Handle types of the namespace and file descriptors.
+pub const PA_NS_DIR: u8 = 0x20;
+pub const PA_FD: u8 = 0x30;
+pub const PA_DIRECTORY_REQUEST: u8 = 0x3B;
+pub const PA_RESOURCE: u8 = 0x3F;
+// Handle types reserved for application use.
+pub const PA_USER0: u8 = 0xF0;
+pub const PA_USER1: u8 = 0xF1;
+pub const PA_USER2: u8 = 0xF2;
+
+/// The tag of a handle in processargs, i.e. `PA_HND(type, arg)`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct HandleInfo(pub u32);
+
+impl HandleInfo {
+    /// Create a tag of handle type `ty` with argument `arg`.
+    pub const fn new(ty: u8, arg: u16) -> Self {
+        HandleInfo(ty as u32 | (arg as u32) << 16)
+    }
+
+    /// The handle type, one of `PA_*`.
+    pub fn handle_type(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// The argument, e.g. the fd number of `PA_FD`.
+    pub fn arg(self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+}
+
+/// The header of a processargs message, i.e. `zx_proc_args_t`.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+struct ProcArgsHeader {
+    protocol: u32,
+    version: u32,
+    handle_info_off: u32,
+    args_off: u32,
+    args_num: u32,
+    environ_off: u32,
+    environ_num: u32,
+    names_off: u32,
+    names_num: u32,
+}
+
+const HEADER_SIZE: usize = size_of::<ProcArgsHeader>();
+
+/// Builder of a processargs message.
+#[derive(Default)]
+pub struct ProcArgsBuilder {
+    handles: Vec<Handle>,
+    handle_info: Vec<HandleInfo>,
+    args: Vec<String>,
+    environ: Vec<String>,
+    names: Vec<String>,
+}
+
+impl ProcArgsBuilder {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a handle tagged with `info`.
+    pub fn handle(mut self, info: HandleInfo, handle: Handle) -> Self {
+        self.handles.push(handle);
+        self.handle_info.push(info);
+        self
+    }
+
+    /// Add command line arguments.
+    pub fn args<S: Into<String>>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add environment variables, each in the form of `KEY=VALUE`.
+    pub fn environ<S: Into<String>>(mut self, environ: impl IntoIterator<Item = S>) -> Self {
+        self.environ.extend(environ.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add namespace paths, referred by the argument of `PA_NS_DIR` handles.
+    pub fn names<S: Into<String>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.names.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Build the message.
+    pub fn build(self) -> MessagePacket {
+        let args = encode_strings(&self.args);
+        let environ = encode_strings(&self.environ);
+        let names = encode_strings(&self.names);
+        let handle_info_off = HEADER_SIZE;
+        let args_off = handle_info_off + self.handle_info.len() * size_of::<u32>();
+        let environ_off = args_off + args.len();
+        let names_off = environ_off + environ.len();
+        let header = ProcArgsHeader {
+            protocol: PA_PROTOCOL,
+            version: PA_VERSION,
+            handle_info_off: handle_info_off as u32,
+            args_off: args_off as u32,
+            args_num: self.args.len() as u32,
+            environ_off: environ_off as u32,
+            environ_num: self.environ.len() as u32,
+            names_off: names_off as u32,
+            names_num: self.names.len() as u32,
+        };
+        let mut data = Vec::with_capacity(names_off + names.len());
+        for field in header.as_array().iter() {
+            data.extend_from_slice(&field.to_ne_bytes());
+        }
+        for info in self.handle_info.iter() {
+            data.extend_from_slice(&info.0.to_ne_bytes());
+        }
+        data.extend(args);
+        data.extend(environ);
+        data.extend(names);
+        MessagePacket {
+            data,
+            handles: self.handles,
+        }
+    }
+}
+
+/// A parsed processargs message, without its handles.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ProcArgs {
+    /// The tags of handles, in the order of the handles in the message.
+    pub handle_info: Vec<HandleInfo>,
+    /// Command line arguments.
+    pub args: Vec<String>,
+    /// Environment variables.
+    pub environ: Vec<String>,
+    /// Namespace paths.
+    pub names: Vec<String>,
+}
+
+impl ProcArgs {
+    /// Parse the data of a processargs message with `num_handles` handles.
+    ///
+    /// Return `INVALID_ARGS` if the message is malformed.
+    pub fn parse(data: &[u8], num_handles: usize) -> ZxResult<Self> {
+        let header = ProcArgsHeader::from_bytes(data)?;
+        if header.protocol != PA_PROTOCOL || header.version != PA_VERSION {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let off = header.handle_info_off as usize;
+        let end = num_handles
+            .checked_mul(size_of::<u32>())
+            .and_then(|len| off.checked_add(len))
+            .ok_or(ZxError::INVALID_ARGS)?;
+        let info_data = data.get(off..end).ok_or(ZxError::INVALID_ARGS)?;
+        let handle_info = info_data
+            .chunks_exact(size_of::<u32>())
+            .map(|b| HandleInfo(u32::from_ne_bytes([b[0], b[1], b[2], b[3]])))
+            .collect();
+        Ok(ProcArgs {
+            handle_info,
+            args: decode_strings(data, header.args_off, header.args_num)?,
+            environ: decode_strings(data, header.environ_off, header.environ_num)?,
+            names: decode_strings(data, header.names_off, header.names_num)?,
+        })
+    }
+
+    /// Find the index of the first handle tagged with `info`.
+    pub fn find_handle(&self, info: HandleInfo) -> Option<usize> {
+        self.handle_info.iter().position(|&i| i == info)
+    }
+}
+
+impl ProcArgsHeader {
+    fn as_array(&self) -> [u32; 9] {
+        [
+            self.protocol,
+            self.version,
+            self.handle_info_off,
+            self.args_off,
+            self.args_num,
+            self.environ_off,
+            self.environ_num,
+            self.names_off,
+            self.names_num,
+        ]
+    }
+
+    fn from_bytes(data: &[u8]) -> ZxResult<Self> {
+        let data = data.get(..HEADER_SIZE).ok_or(ZxError::INVALID_ARGS)?;
+        let mut fields = data
+            .chunks_exact(size_of::<u32>())
+            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]));
+        let mut next = || fields.next().unwrap();
+        Ok(ProcArgsHeader {
+            protocol: next(),
+            version: next(),
+            handle_info_off: next(),
+            args_off: next(),
+            args_num: next(),
+            environ_off: next(),
+            environ_num: next(),
+            names_off: next(),
+            names_num: next(),
+        })
+    }
+}
+
+/// Encode strings as a sequence of NUL-terminated strings.
+pub fn encode_strings<S: AsRef<str>>(strings: impl IntoIterator<Item = S>) -> Vec<u8> {
+    let mut data = Vec::new();
+    for s in strings {
+        data.extend_from_slice(s.as_ref().as_bytes());
+        data.push(0);
+    }
+    data
+}
+
+/// Decode `num` NUL-terminated strings starting at `off`.
+fn decode_strings(data: &[u8], off: u32, num: u32) -> ZxResult<Vec<String>> {
+    if num == 0 {
+        return Ok(Vec::new());
+    }
+    let mut data = data.get(off as usize..).ok_or(ZxError::INVALID_ARGS)?;
+    // every string takes at least one byte, which also bounds the allocation
+    if num as usize > data.len() {
+        return Err(ZxError::INVALID_ARGS);
+    }
+    let mut strings = Vec::with_capacity(num as usize);
+    for _ in 0..num {
+        let len = data
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(ZxError::INVALID_ARGS)?;
+        let s = core::str::from_utf8(&data[..len]).map_err(|_| ZxError::INVALID_ARGS)?;
+        strings.push(String::from(s));
+        data = &data[len + 1..];
+    }
+    Ok(strings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zircon_object::object::{DummyObject, Rights};
+
+    fn build() -> MessagePacket {
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        ProcArgsBuilder::new()
+            .handle(HandleInfo::new(PA_PROC_SELF, 0), handle.clone())
+            .handle(HandleInfo::new(PA_NS_DIR, 1), handle)
+            .args(vec!["bin/sh", "-c"])
+            .environ(vec!["HOME=/"])
+            .names(vec!["/pkg", "/svc"])
+            .build()
+    }
+
+    #[test]
+    fn round_trip() {
+        let msg = build();
+        let args = ProcArgs::parse(&msg.data, msg.handles.len()).unwrap();
+        assert_eq!(
+            args.handle_info,
+            [
+                HandleInfo::new(PA_PROC_SELF, 0),
+                HandleInfo::new(PA_NS_DIR, 1)
+            ]
+        );
+        assert_eq!(args.args, ["bin/sh", "-c"]);
+        assert_eq!(args.environ, ["HOME=/"]);
+        assert_eq!(args.names, ["/pkg", "/svc"]);
+        assert_eq!(args.find_handle(HandleInfo::new(PA_NS_DIR, 1)), Some(1));
+        assert_eq!(args.handle_info[1].handle_type(), PA_NS_DIR);
+        assert_eq!(args.handle_info[1].arg(), 1);
+
+        let empty = ProcArgsBuilder::new().build();
+        assert_eq!(ProcArgs::parse(&empty.data, 0), Ok(ProcArgs::default()));
+    }
+
+    #[test]
+    fn malformed() {
+        let msg = build();
+        let parse = |data: &[u8]| ProcArgs::parse(data, msg.handles.len()).err();
+
+        // bad magic
+        let mut data = msg.data.clone();
+        data[0] ^= 1;
+        assert_eq!(parse(&data), Some(ZxError::INVALID_ARGS));
+
+        // truncated header, handle info or strings
+        for len in [HEADER_SIZE - 1, HEADER_SIZE + 4, msg.data.len() - 1].iter() {
+            assert_eq!(parse(&msg.data[..*len]), Some(ZxError::INVALID_ARGS));
+        }
+
+        // missing NUL of the last string
+        let mut data = msg.data.clone();
+        *data.last_mut().unwrap() = b'x';
+        assert_eq!(parse(&data), Some(ZxError::INVALID_ARGS));
+
+        // too many handles or strings
+        assert_eq!(
+            ProcArgs::parse(&msg.data, usize::MAX).err(),
+            Some(ZxError::INVALID_ARGS)
+        );
+        let mut data = msg.data.clone();
+        data[16..20].copy_from_slice(&u32::MAX.to_ne_bytes());
+        assert_eq!(parse(&data), Some(ZxError::INVALID_ARGS));
+    }
+}
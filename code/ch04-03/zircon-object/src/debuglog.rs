@@ -35,11 +35,11 @@ impl_kobject!(DebugLog);
 impl DebugLog {
     /// Create a new `DebugLog`.
     pub fn create(flags: u32) -> Arc<Self> {
-        Arc::new(DebugLog {
+        register_object(Arc::new(DebugLog {
             base: KObjectBase::new(),
             flags,
             read_offset: Default::default(),
-        })
+        }))
     }
 
     /// Read a log, return the actual read size.
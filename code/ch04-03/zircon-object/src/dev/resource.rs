@@ -47,13 +47,13 @@ impl Resource {
         len: usize,
         flags: ResourceFlags,
     ) -> Arc<Self> {
-        Arc::new(Resource {
+        register_object(Arc::new(Resource {
             base: KObjectBase::with_name(name),
             kind,
             addr,
             len,
             flags,
-        })
+        }))
     }
 
     /// Validate the resource is the given kind or it is the root resource.
@@ -37,6 +37,15 @@ pub enum ZxError {
     /// retried.  This should not be seen outside of the VDSO.
     INTERNAL_INTR_RETRY = -6,
 
+    /// The system call was interrupted because the thread is being killed.
+    /// This should not be seen outside of the kernel.
+    INTERNAL_INTR_KILLED = -502,
+
+    /// A handle quota of the process or its ancestor jobs is exceeded.
+    /// This should not be seen outside of the kernel,
+    /// where it is reported as `NO_RESOURCES`.
+    INTERNAL_QUOTA_EXCEEDED = -503,
+
     // ======= Parameter errors =======
     /// an argument is invalid, ex. null pointer
     INVALID_ARGS = -10,
@@ -2,24 +2,123 @@ use {
     super::*,
     crate::error::*,
     crate::object::*,
+    crate::util::kcounter::KCounter,
+    alloc::boxed::Box,
     alloc::collections::VecDeque,
     alloc::sync::{Arc, Weak},
     alloc::vec::Vec,
     core::convert::TryInto,
+    core::future::Future,
+    core::pin::Pin,
     core::sync::atomic::{AtomicU32, Ordering},
+    core::task::{Context, Poll},
+    futures::channel::oneshot::{self, Sender},
+    hashbrown::HashMap,
     spin::Mutex,
 };
 
 pub struct Channel {
     base: KObjectBase,
     peer: Weak<Channel>,
-    recv_queue: Mutex<VecDeque<T>>,
+    recv_queue: Mutex<MessageQueue>,
+    call_reply: Mutex<HashMap<TxID, Sender<ZxResult<T>>>>,
     next_txid: AtomicU32,
 }
 
 type T = MessagePacket;
 type TxID = u32;
 
+/// The most messages pending in a channel endpoint seen so far.
+pub static CHANNEL_MESSAGES_HIGH_WATER: KCounter = KCounter::new("channel.messages.high_water");
+/// The most bytes pending in a channel endpoint seen so far.
+pub static CHANNEL_BYTES_HIGH_WATER: KCounter = KCounter::new("channel.bytes.high_water");
+
+/// Limits of the messages pending in a channel endpoint.
+///
+/// Writing to a full endpoint fails with `SHOULD_WAIT`,
+/// and the writer is not `WRITABLE` until the reader frees up space.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ChannelLimits {
+    /// The maximum number of pending messages.
+    pub max_messages: usize,
+    /// The maximum number of pending data bytes.
+    ///
+    /// A message larger than this is accepted only when the queue is empty.
+    pub max_bytes: usize,
+}
+
+impl Default for ChannelLimits {
+    fn default() -> Self {
+        ChannelLimits {
+            max_messages: 3500,
+            max_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// The receive queue of a channel endpoint.
+#[derive(Default)]
+struct MessageQueue {
+    msgs: VecDeque<T>,
+    bytes: usize,
+    limits: ChannelLimits,
+}
+
+impl MessageQueue {
+    /// Is there room for another message?
+    fn has_space(&self) -> bool {
+        self.msgs.len() < self.limits.max_messages && self.bytes < self.limits.max_bytes
+    }
+
+    fn push(&mut self, msg: T) -> ZxResult {
+        let fits = self.msgs.len() < self.limits.max_messages
+            && self.bytes + msg.data.len() <= self.limits.max_bytes;
+        if !self.msgs.is_empty() && !fits {
+            return Err(ZxError::SHOULD_WAIT);
+        }
+        self.bytes += msg.data.len();
+        self.msgs.push_back(msg);
+        CHANNEL_MESSAGES_HIGH_WATER.update_max(self.msgs.len());
+        CHANNEL_BYTES_HIGH_WATER.update_max(self.bytes);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let msg = self.msgs.pop_front()?;
+        self.bytes -= msg.data.len();
+        Some(msg)
+    }
+}
+
+/// The future of the reply to a `call`.
+pub type CallFuture = Pin<Box<dyn Future<Output = ZxResult<MessagePacket>> + Send + 'static>>;
+
+/// The reply to a `call`, which gives up the txid when dropped before the reply arrives.
+struct CallReply {
+    channel: Weak<Channel>,
+    txid: TxID,
+    receiver: oneshot::Receiver<ZxResult<T>>,
+}
+
+impl Future for CallReply {
+    type Output = ZxResult<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        // the sender is dropped when the peer is closed
+        Pin::new(&mut self.receiver)
+            .poll(cx)
+            .map(|ret| ret.unwrap_or(Err(ZxError::PEER_CLOSED)))
+    }
+}
+
+impl Drop for CallReply {
+    fn drop(&mut self) {
+        if let Some(channel) = self.channel.upgrade() {
+            channel.call_reply.lock().remove(&self.txid);
+        }
+    }
+}
+
 impl_kobject!(Channel
     fn peer(&self) -> ZxResult<Arc<dyn KernelObject>> {
         let peer = self.peer.upgrade().ok_or(ZxError::PEER_CLOSED)?;
@@ -32,33 +131,55 @@ impl_kobject!(Channel
 
 impl Channel {
     /// Create a channel and return a pair of its endpoints
-    #[allow(unsafe_code)]
     pub fn create() -> (Arc<Self>, Arc<Self>) {
+        Self::create_with_limits(ChannelLimits::default())
+    }
+
+    /// Create a channel whose endpoints both have `limits`.
+    #[allow(unsafe_code)]
+    pub fn create_with_limits(limits: ChannelLimits) -> (Arc<Self>, Arc<Self>) {
+        let queue = || {
+            Mutex::new(MessageQueue {
+                limits,
+                ..Default::default()
+            })
+        };
         let mut channel0 = Arc::new(Channel {
-            base: KObjectBase::default(),
+            base: KObjectBase::with_signal(Signal::WRITABLE),
             peer: Weak::default(),
-            recv_queue: Default::default(),
+            recv_queue: queue(),
+            call_reply: Default::default(),
             next_txid: AtomicU32::new(0x8000_0000),
         });
         let channel1 = Arc::new(Channel {
-            base: KObjectBase::default(),
+            base: KObjectBase::with_signal(Signal::WRITABLE),
             peer: Arc::downgrade(&channel0),
-            recv_queue: Default::default(),
+            recv_queue: queue(),
+            call_reply: Default::default(),
             next_txid: AtomicU32::new(0x8000_0000),
         });
         // no other reference of `channel0`
         unsafe {
             Arc::get_mut_unchecked(&mut channel0).peer = Arc::downgrade(&channel1);
         }
-        (channel0, channel1)
+        (register_object(channel0), register_object(channel1))
     }
 
     /// Read a packet from the channel if check is ok, otherwise the msg will keep.
     pub fn check_and_read(&self, checker: impl FnOnce(&T) -> ZxResult) -> ZxResult<T> {
         let mut recv_queue = self.recv_queue.lock();
-        if let Some(msg) = recv_queue.front() {
+        if let Some(msg) = recv_queue.msgs.front() {
             checker(msg)?;
-            let msg = recv_queue.pop_front().unwrap();
+            let had_space = recv_queue.has_space();
+            let msg = recv_queue.pop().unwrap();
+            if recv_queue.msgs.is_empty() {
+                self.base.signal_clear(Signal::READABLE);
+            }
+            if !had_space && recv_queue.has_space() {
+                if let Some(peer) = self.peer.upgrade() {
+                    peer.base.signal_set(Signal::WRITABLE);
+                }
+            }
             return Ok(msg);
         }
         if self.peer_closed() {
@@ -74,16 +195,80 @@ impl Channel {
     }
 
     /// Write a packet to the channel
-    pub fn write(&self, msg: T) -> ZxResult {
+    ///
+    /// If the txid of the packet matches a pending `call` of the peer,
+    /// it is routed to the caller instead of the general queue.
+    ///
+    /// Return `SHOULD_WAIT` if the queue of the peer is full.
+    pub fn write(&self, mut msg: T) -> ZxResult {
         let peer = self.peer.upgrade().ok_or(ZxError::PEER_CLOSED)?;
-        peer.push_general(msg);
-        Ok(())
+        let sender = peer.call_reply.lock().remove(&msg.get_txid());
+        if let Some(sender) = sender {
+            match sender.send(Ok(msg)) {
+                Ok(()) => return Ok(()),
+                // the caller has given up waiting, e.g. timed out
+                Err(ret) => msg = ret.unwrap(),
+            }
+        }
+        peer.push_general(msg)
+    }
+
+    /// Send a message to the channel and return a future of the reply.
+    ///
+    /// The kernel allocates a new txid and stamps it into the message.
+    /// The reply is the message written by the peer with the same txid.
+    pub fn call(&self, mut msg: T) -> ZxResult<CallFuture> {
+        let peer = self.peer.upgrade().ok_or(ZxError::PEER_CLOSED)?;
+        let txid = self.new_txid();
+        msg.set_txid(txid);
+        let (sender, receiver) = oneshot::channel();
+        self.call_reply.lock().insert(txid, sender);
+        if let Err(err) = peer.push_general(msg) {
+            self.call_reply.lock().remove(&txid);
+            return Err(err);
+        }
+        Ok(Box::pin(CallReply {
+            channel: peer.peer.clone(),
+            txid,
+            receiver,
+        }))
+    }
+
+    /// Set the limits of messages pending in this endpoint.
+    ///
+    /// Messages already in the queue are kept even if they exceed the new limits.
+    pub fn set_limits(&self, limits: ChannelLimits) {
+        let mut recv_queue = self.recv_queue.lock();
+        recv_queue.limits = limits;
+        if let Some(peer) = self.peer.upgrade() {
+            if recv_queue.has_space() {
+                peer.base.signal_set(Signal::WRITABLE);
+            } else {
+                peer.base.signal_clear(Signal::WRITABLE);
+            }
+        }
+    }
+
+    /// Get the limits of messages pending in this endpoint.
+    pub fn limits(&self) -> ChannelLimits {
+        self.recv_queue.lock().limits
     }
 
     /// Push a message to general queue, called from peer.
-    fn push_general(&self, msg: T) {
+    ///
+    /// The peer is no longer `WRITABLE` once the queue is full.
+    fn push_general(&self, msg: T) -> ZxResult {
         let mut send_queue = self.recv_queue.lock();
-        send_queue.push_back(msg);
+        send_queue.push(msg)?;
+        if send_queue.msgs.len() == 1 {
+            self.base.signal_set(Signal::READABLE);
+        }
+        if !send_queue.has_space() {
+            if let Some(peer) = self.peer.upgrade() {
+                peer.base.signal_clear(Signal::WRITABLE);
+            }
+        }
+        Ok(())
     }
 
     /// Generate a new transaction ID for `call`.
@@ -97,6 +282,17 @@ impl Channel {
     }
 }
 
+impl Drop for Channel {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer.upgrade() {
+            peer.base
+                .signal_change(Signal::WRITABLE, Signal::PEER_CLOSED);
+            // wake up all pending calls of the peer with `PEER_CLOSED`
+            peer.call_reply.lock().clear();
+        }
+    }
+}
+
 /// The message transferred in the channel.
 /// See [Channel](struct.Channel.html) for details.
 #[derive(Default)]
@@ -128,6 +324,8 @@ impl MessagePacket {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::boxed::Box;
+    use core::sync::atomic::AtomicBool;
 
     #[test]
     fn test_basics() {
@@ -174,6 +372,127 @@ mod tests {
         assert_eq!(channel1.read().err(), Some(ZxError::SHOULD_WAIT));
     }
 
+    #[test]
+    fn signal() {
+        let (channel0, channel1) = Channel::create();
+
+        // initial status is writable and not readable.
+        let init_signal = channel0.base.signal();
+        assert!(!init_signal.contains(Signal::READABLE));
+        assert!(init_signal.contains(Signal::WRITABLE));
+
+        // register callback for `Signal::READABLE` & `Signal::PEER_CLOSED`:
+        //   set `readable` and `peer_closed`
+        let readable = Arc::new(AtomicBool::new(false));
+        let peer_closed = Arc::new(AtomicBool::new(false));
+        channel0.add_signal_callback(Box::new({
+            let readable = readable.clone();
+            let peer_closed = peer_closed.clone();
+            move |signal| {
+                readable.store(signal.contains(Signal::READABLE), Ordering::SeqCst);
+                peer_closed.store(signal.contains(Signal::PEER_CLOSED), Ordering::SeqCst);
+                false
+            }
+        }));
+
+        // writing to peer should trigger `Signal::READABLE`.
+        channel1.write(MessagePacket::default()).unwrap();
+        assert!(readable.load(Ordering::SeqCst));
+
+        // reading all messages should cause `Signal::READABLE` be cleared.
+        channel0.read().unwrap();
+        assert!(!readable.load(Ordering::SeqCst));
+
+        // peer closed should trigger `Signal::PEER_CLOSED`.
+        assert!(!peer_closed.load(Ordering::SeqCst));
+        drop(channel1);
+        assert!(peer_closed.load(Ordering::SeqCst));
+        assert!(!channel0.signal().contains(Signal::WRITABLE));
+    }
+
+    #[async_std::test]
+    async fn call() {
+        let (channel0, channel1) = Channel::create();
+        let msg = |data: &[u8]| MessagePacket {
+            data: Vec::from(data),
+            handles: Vec::new(),
+        };
+        let reply = channel0.call(msg(b"txid0001")).unwrap();
+
+        // a message with other txid goes to the general queue
+        channel1.write(msg(b"txid0002")).unwrap();
+        let request = channel1.read().unwrap();
+        assert_eq!(&request.data[4..], b"0001");
+        let mut response = msg(b"txid0003");
+        response.set_txid(request.get_txid());
+        channel1.write(response).unwrap();
+
+        assert_eq!(&reply.await.unwrap().data[4..], b"0003");
+        assert_eq!(&channel0.read().unwrap().data[4..], b"0002");
+        assert_eq!(channel0.read().err(), Some(ZxError::SHOULD_WAIT));
+
+        // the txid is given up when the caller stops waiting
+        let reply = channel0.call(msg(b"txid0004")).unwrap();
+        drop(reply);
+        assert!(channel0.call_reply.lock().is_empty());
+        let request = channel1.read().unwrap();
+        let mut response = msg(b"txid0005");
+        response.set_txid(request.get_txid());
+        channel1.write(response).unwrap();
+        assert_eq!(&channel0.read().unwrap().data[4..], b"0005");
+
+        // pending calls fail when peer is closed
+        let reply = channel0.call(msg(b"txid0006")).unwrap();
+        drop(channel1);
+        assert_eq!(reply.await.err(), Some(ZxError::PEER_CLOSED));
+    }
+
+    #[test]
+    fn bounded() {
+        let (channel0, channel1) = Channel::create_with_limits(ChannelLimits {
+            max_messages: 2,
+            max_bytes: 8,
+        });
+        let msg = |len| MessagePacket {
+            data: vec![0; len],
+            handles: Vec::new(),
+        };
+        // an oversized message is accepted by an empty queue
+        channel0.write(msg(10)).unwrap();
+        assert!(!channel0.signal().contains(Signal::WRITABLE));
+        assert_eq!(channel0.write(msg(0)), Err(ZxError::SHOULD_WAIT));
+        channel1.read().unwrap();
+        assert!(channel0.signal().contains(Signal::WRITABLE));
+
+        // limit by messages
+        channel0.write(msg(1)).unwrap();
+        channel0.write(msg(1)).unwrap();
+        assert!(!channel0.signal().contains(Signal::WRITABLE));
+        assert_eq!(channel0.write(msg(1)), Err(ZxError::SHOULD_WAIT));
+        assert_eq!(channel0.call(msg(4)).err(), Some(ZxError::SHOULD_WAIT));
+        assert!(channel0.call_reply.lock().is_empty());
+        channel1.read().unwrap();
+        assert!(channel0.signal().contains(Signal::WRITABLE));
+
+        // limit by bytes
+        assert_eq!(channel0.write(msg(8)), Err(ZxError::SHOULD_WAIT));
+        channel0.write(msg(7)).unwrap();
+        assert!(!channel0.signal().contains(Signal::WRITABLE));
+        assert!(CHANNEL_MESSAGES_HIGH_WATER.get() >= 2);
+        assert!(CHANNEL_BYTES_HIGH_WATER.get() >= 10);
+
+        // raising the limits makes the writer writable again
+        channel1.set_limits(ChannelLimits {
+            max_messages: 3,
+            max_bytes: 16,
+        });
+        assert!(channel0.signal().contains(Signal::WRITABLE));
+        channel0.write(msg(8)).unwrap();
+        assert_eq!(channel1.read().unwrap().data.len(), 1);
+        assert_eq!(channel1.read().unwrap().data.len(), 7);
+        assert_eq!(channel1.read().unwrap().data.len(), 8);
+    }
+
     #[test]
     fn peer_closed() {
         let (channel0, channel1) = Channel::create();
@@ -1,4 +1,6 @@
 use super::*;
 
 mod channel;
-pub use self::channel::*;
+mod port;
+mod port_packet;
+pub use self::{channel::*, port::*, port_packet::*};
@@ -0,0 +1,316 @@
+use {
+    super::*,
+    crate::object::*,
+    alloc::boxed::Box,
+    alloc::collections::VecDeque,
+    alloc::sync::{Arc, Weak},
+    core::sync::atomic::{AtomicBool, Ordering},
+    spin::Mutex,
+};
+
+/// The maximum number of packets queued in a port.
+///
+/// Each async wait on the port reserves one slot, so that its signal packets
+/// are never dropped. Repeating waits keep their slots until they are removed.
+pub const MAX_PORT_PACKETS: usize = 4096;
+
+/// Signaling and mailbox primitive
+///
+/// ## SYNOPSIS
+///
+/// Ports allow threads to wait for packets to be delivered from various
+/// events. These events include explicit queueing on the port,
+/// asynchronous waits on other handles bound to the port.
+pub struct Port {
+    base: KObjectBase,
+    inner: Mutex<PortInner>,
+}
+
+impl_kobject!(Port);
+
+#[derive(Default)]
+struct PortInner {
+    queue: VecDeque<PortPacketRepr>,
+    /// The number of slots reserved by async waits.
+    reserved: usize,
+}
+
+impl Port {
+    /// Create a new `Port`.
+    pub fn new() -> Arc<Self> {
+        register_object(Arc::new(Port {
+            base: KObjectBase::default(),
+            inner: Mutex::default(),
+        }))
+    }
+
+    /// Push a `packet` into the port.
+    ///
+    /// Return `SHOULD_WAIT` if the queue is full.
+    pub fn push(&self, packet: PortPacketRepr) -> ZxResult {
+        self.push_packet(packet, false)
+    }
+
+    /// Push a `packet` into the port, either in a free slot or in a `reserved` one.
+    fn push_packet(&self, packet: PortPacketRepr, reserved: bool) -> ZxResult {
+        let mut inner = self.inner.lock();
+        // coalesce repeating signal packets from the same observer
+        if let PayloadRepr::SignalRep(new, koid) = packet.data {
+            for queued in inner.queue.iter_mut() {
+                match &mut queued.data {
+                    PayloadRepr::SignalRep(old, old_koid)
+                        if queued.key == packet.key && *old_koid == koid =>
+                    {
+                        old.observed = new.observed;
+                        old.count += new.count;
+                        return Ok(());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !reserved && inner.queue.len() + inner.reserved >= MAX_PORT_PACKETS {
+            return Err(ZxError::SHOULD_WAIT);
+        }
+        inner.queue.push_back(packet);
+        drop(inner);
+        self.base.signal_set(Signal::READABLE);
+        Ok(())
+    }
+
+    /// Asynchronous wait until at least one packet is available, then take out the first one.
+    pub async fn wait(self: &Arc<Self>) -> PortPacketRepr {
+        let object = self.clone() as Arc<dyn KernelObject>;
+        loop {
+            object.wait_signal(Signal::READABLE).await;
+            let mut inner = self.inner.lock();
+            if let Some(packet) = inner.queue.pop_front() {
+                if inner.queue.is_empty() {
+                    self.base.signal_clear(Signal::READABLE);
+                }
+                return packet;
+            }
+            self.base.signal_clear(Signal::READABLE);
+        }
+    }
+
+    /// Get the number of packets in queue.
+    pub fn len(&self) -> usize {
+        self.inner.lock().queue.len()
+    }
+
+    /// Returns true if there is no packet in queue.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reserve a slot for the packets of an async wait.
+    ///
+    /// Return `SHOULD_WAIT` if the queue is full.
+    fn reserve(self: &Arc<Self>) -> ZxResult<ReservedSlot> {
+        let mut inner = self.inner.lock();
+        if inner.queue.len() + inner.reserved >= MAX_PORT_PACKETS {
+            return Err(ZxError::SHOULD_WAIT);
+        }
+        inner.reserved += 1;
+        Ok(ReservedSlot(Arc::downgrade(self)))
+    }
+}
+
+/// A slot of a port reserved by an async wait, released when dropped.
+struct ReservedSlot(Weak<Port>);
+
+impl Drop for ReservedSlot {
+    fn drop(&mut self) {
+        if let Some(port) = self.0.upgrade() {
+            port.inner.lock().reserved -= 1;
+        }
+    }
+}
+
+impl dyn KernelObject {
+    /// Push a signal packet with `key` into the `port` once one of the `signal` asserted.
+    ///
+    /// If `repeating`, a packet is pushed every time the `signal` turns asserted again,
+    /// until the port is destroyed.
+    ///
+    /// A slot of the `port` is reserved for the packets until the wait is removed,
+    /// return `SHOULD_WAIT` if the port is full.
+    ///
+    /// It's used to implement `sys_object_wait_async`.
+    pub fn send_signal_to_port_async(
+        self: &Arc<Self>,
+        signal: Signal,
+        port: &Arc<Port>,
+        key: u64,
+        repeating: bool,
+    ) -> ZxResult {
+        let koid = self.id();
+        let packet = move |observed| PortPacketRepr {
+            key,
+            status: ZxError::OK,
+            data: {
+                let signal = PacketSignal {
+                    trigger: signal,
+                    observed,
+                    count: 1,
+                    ..Default::default()
+                };
+                if repeating {
+                    PayloadRepr::SignalRep(signal, koid)
+                } else {
+                    PayloadRepr::SignalOne(signal)
+                }
+            },
+        };
+        let slot = port.reserve()?;
+        // only edges from deasserted to asserted are reported for repeating waits
+        let asserted = AtomicBool::new(false);
+        self.add_signal_callback(Box::new(move |s| {
+            let port = match Weak::upgrade(&slot.0) {
+                Some(port) => port,
+                None => return true,
+            };
+            if (s & signal).is_empty() {
+                asserted.store(false, Ordering::SeqCst);
+                return false;
+            }
+            if asserted.swap(true, Ordering::SeqCst) {
+                return false;
+            }
+            // pushing into the reserved slot never fails
+            port.push_packet(packet(s), true).unwrap();
+            !repeating
+        }));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object::DummyObject;
+
+    fn user_packet(key: u64) -> PortPacketRepr {
+        PortPacketRepr {
+            key,
+            status: ZxError::OK,
+            data: PayloadRepr::User([key as u8; 32]),
+        }
+    }
+
+    #[async_std::test]
+    async fn push_wait() {
+        let port = Port::new();
+        assert!(!port.signal().contains(Signal::READABLE));
+
+        port.push(user_packet(1)).unwrap();
+        port.push(user_packet(2)).unwrap();
+        assert!(port.signal().contains(Signal::READABLE));
+
+        assert_eq!(port.wait().await, user_packet(1));
+        assert_eq!(port.wait().await, user_packet(2));
+        assert!(!port.signal().contains(Signal::READABLE));
+        assert!(port.is_empty());
+    }
+
+    #[test]
+    fn bounded() {
+        let port = Port::new();
+        for i in 0..MAX_PORT_PACKETS {
+            port.push(user_packet(i as u64)).unwrap();
+        }
+        assert_eq!(port.push(user_packet(0)), Err(ZxError::SHOULD_WAIT));
+        assert_eq!(port.len(), MAX_PORT_PACKETS);
+    }
+
+    #[test]
+    fn wait_async_reserved() {
+        let port = Port::new();
+        let object = DummyObject::new() as Arc<dyn KernelObject>;
+        for i in 0..MAX_PORT_PACKETS - 1 {
+            port.push(user_packet(i as u64)).unwrap();
+        }
+        object
+            .send_signal_to_port_async(Signal::READABLE, &port, 1, false)
+            .unwrap();
+        // the last slot is reserved by the wait
+        assert_eq!(port.push(user_packet(0)), Err(ZxError::SHOULD_WAIT));
+        assert_eq!(
+            object
+                .send_signal_to_port_async(Signal::READABLE, &port, 2, false)
+                .err(),
+            Some(ZxError::SHOULD_WAIT)
+        );
+
+        object.signal_set(Signal::READABLE);
+        assert_eq!(port.len(), MAX_PORT_PACKETS);
+        assert_eq!(port.inner.lock().reserved, 0);
+    }
+
+    #[async_std::test]
+    async fn wait_async_once() {
+        let port = Port::new();
+        let object = DummyObject::new() as Arc<dyn KernelObject>;
+        object
+            .send_signal_to_port_async(Signal::READABLE, &port, 1, false)
+            .unwrap();
+
+        object.signal_set(Signal::WRITABLE);
+        assert!(port.is_empty());
+
+        object.signal_set(Signal::READABLE);
+        let packet = port.wait().await;
+        assert_eq!(
+            packet,
+            PortPacketRepr {
+                key: 1,
+                status: ZxError::OK,
+                data: PayloadRepr::SignalOne(PacketSignal {
+                    trigger: Signal::READABLE,
+                    observed: Signal::READABLE | Signal::WRITABLE,
+                    count: 1,
+                    ..Default::default()
+                }),
+            }
+        );
+
+        // one-shot wait should be removed after triggered
+        object.signal_clear(Signal::READABLE);
+        object.signal_set(Signal::READABLE);
+        assert!(port.is_empty());
+    }
+
+    #[test]
+    fn wait_async_repeating() {
+        let port = Port::new();
+        let object = DummyObject::new() as Arc<dyn KernelObject>;
+        object
+            .send_signal_to_port_async(Signal::READABLE, &port, 2, true)
+            .unwrap();
+
+        // two edges before the packet is dequeued are coalesced
+        object.signal_set(Signal::READABLE);
+        object.signal_set(Signal::WRITABLE);
+        object.signal_clear(Signal::READABLE);
+        object.signal_set(Signal::READABLE);
+        assert_eq!(port.len(), 1);
+        let packet = port.inner.lock().queue.pop_front().unwrap();
+        match packet.data {
+            PayloadRepr::SignalRep(signal, koid) => {
+                assert_eq!(signal.count, 2);
+                assert_eq!(signal.observed, Signal::READABLE | Signal::WRITABLE);
+                assert_eq!(koid, object.id());
+            }
+            _ => panic!("unexpected packet: {:?}", packet),
+        }
+
+        object.signal_clear(Signal::READABLE);
+        object.signal_set(Signal::READABLE);
+        assert_eq!(port.len(), 1);
+
+        // the observer is removed after the port is destroyed
+        drop(port);
+        object.signal_clear(Signal::READABLE);
+    }
+}
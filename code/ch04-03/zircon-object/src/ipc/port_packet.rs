@@ -0,0 +1,127 @@
+use {
+    super::*,
+    crate::object::{KoID, Signal},
+    core::fmt::{Debug, Formatter},
+    numeric_enum_macro::numeric_enum,
+};
+
+/// A packet sent through a port.
+///
+/// This is the ABI of `zx_port_packet_t` in userspace.
+#[repr(C)]
+pub struct PortPacket {
+    /// The key of the packet, chosen by the sender.
+    pub key: u64,
+    /// The type of the packet, decides how to interpret `data`.
+    pub type_: PacketType,
+    /// The status of the packet.
+    pub status: ZxError,
+    /// The payload of the packet.
+    pub data: Payload,
+}
+
+/// The payload of a port packet.
+#[repr(C)]
+pub union Payload {
+    signal: PacketSignal,
+    user: PacketUser,
+}
+
+numeric_enum! {
+    #[repr(u32)]
+    /// The type of a port packet.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub enum PacketType {
+        User = 0,
+        SignalOne = 1,
+        SignalRep = 2,
+    }
+}
+
+/// The payload of a signal packet.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct PacketSignal {
+    /// The signals which the waiter was waiting for.
+    pub trigger: Signal,
+    /// The signals observed when the packet was queued.
+    pub observed: Signal,
+    /// How many times the signals were asserted before the packet was dequeued.
+    pub count: u64,
+    /// Reserved.
+    pub timestamp: u64,
+    /// Reserved.
+    pub _reserved1: u64,
+}
+
+/// The payload of a user packet, defined by userspace.
+pub type PacketUser = [u8; 32];
+
+/// The kernel representation of a port packet.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PortPacketRepr {
+    /// The key of the packet.
+    pub key: u64,
+    /// The status of the packet.
+    pub status: ZxError,
+    /// The payload of the packet.
+    pub data: PayloadRepr,
+}
+
+/// The kernel representation of the payload.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PayloadRepr {
+    /// A one-shot signal packet.
+    SignalOne(PacketSignal),
+    /// A repeating signal packet sent from the object with the koid.
+    SignalRep(PacketSignal, KoID),
+    /// A user packet.
+    User(PacketUser),
+}
+
+impl PayloadRepr {
+    fn type_(&self) -> PacketType {
+        match self {
+            PayloadRepr::SignalOne(_) => PacketType::SignalOne,
+            PayloadRepr::SignalRep(..) => PacketType::SignalRep,
+            PayloadRepr::User(_) => PacketType::User,
+        }
+    }
+}
+
+impl From<PortPacketRepr> for PortPacket {
+    fn from(packet: PortPacketRepr) -> Self {
+        PortPacket {
+            key: packet.key,
+            type_: packet.data.type_(),
+            status: packet.status,
+            data: match packet.data {
+                PayloadRepr::SignalOne(signal) | PayloadRepr::SignalRep(signal, _) => {
+                    Payload { signal }
+                }
+                PayloadRepr::User(user) => Payload { user },
+            },
+        }
+    }
+}
+
+impl From<&PortPacket> for PortPacketRepr {
+    #[allow(unsafe_code)]
+    fn from(packet: &PortPacket) -> Self {
+        PortPacketRepr {
+            key: packet.key,
+            status: packet.status,
+            data: match packet.type_ {
+                PacketType::User => PayloadRepr::User(unsafe { packet.data.user }),
+                PacketType::SignalOne => PayloadRepr::SignalOne(unsafe { packet.data.signal }),
+                PacketType::SignalRep => PayloadRepr::SignalRep(unsafe { packet.data.signal }, 0),
+            },
+        }
+    }
+}
+
+impl Debug for PortPacket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        PortPacketRepr::from(self).fmt(f)
+    }
+}
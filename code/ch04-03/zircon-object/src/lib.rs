@@ -18,6 +18,7 @@ pub mod dev;
 pub mod error;
 pub mod ipc;
 pub mod object;
+pub mod signal;
 pub mod task;
 pub mod util;
 pub mod vm;
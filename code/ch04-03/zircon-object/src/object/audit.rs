@@ -0,0 +1,128 @@
+//! 存活内核对象的审计，用于排查对象泄漏
+//!
+//! 每个内核对象在构造函数中注册时被记录，析构时移除。
+//! 记录中保存类型名、创建位置和对象的弱引用，弱引用仅用于获取当前名称。
+use {
+    super::*, alloc::collections::BTreeMap, alloc::string::String, alloc::sync::Weak,
+    alloc::vec::Vec, core::panic::Location, core::time::Duration, lazy_static::lazy_static,
+    spin::Mutex,
+};
+
+struct Record {
+    type_name: &'static str,
+    created_at: Duration,
+    creator: KoID,
+    location: &'static Location<'static>,
+    object: Weak<dyn KernelObject>,
+}
+
+lazy_static! {
+    static ref LIVE_OBJECTS: Mutex<BTreeMap<KoID, Record>> = Mutex::default();
+}
+
+/// 记录一个新创建的对象
+pub(super) fn on_create(
+    koid: KoID,
+    type_name: &'static str,
+    location: &'static Location<'static>,
+    object: Weak<dyn KernelObject>,
+) {
+    let record = Record {
+        type_name,
+        created_at: kernel_hal::timer_now(),
+        creator: kernel_hal::Thread::get_tid().0,
+        location,
+        object,
+    };
+    LIVE_OBJECTS.lock().insert(koid, record);
+}
+
+/// 移除析构对象的记录
+pub(super) fn on_drop(koid: KoID) {
+    LIVE_OBJECTS.lock().remove(&koid);
+}
+
+/// 一个存活对象的信息
+#[derive(Debug, Clone)]
+pub struct LiveObject {
+    /// 对象 ID
+    pub koid: KoID,
+    /// 对象类型名
+    pub type_name: &'static str,
+    /// 对象名称
+    pub name: String,
+    /// 创建时间
+    pub created_at: Duration,
+    /// 创建该对象的线程 ID，内核创建时为 0
+    pub creator: KoID,
+    /// 创建该对象的代码位置
+    pub location: &'static Location<'static>,
+}
+
+/// 获取所有存活对象，按 ID 排序
+pub fn live_objects() -> Vec<LiveObject> {
+    // 在锁外访问对象，以免释放最后一个引用时在析构中重复加锁
+    let records: Vec<_> = LIVE_OBJECTS
+        .lock()
+        .iter()
+        .map(|(&koid, r)| {
+            let object = r.object.upgrade();
+            (
+                koid,
+                r.type_name,
+                r.created_at,
+                r.creator,
+                r.location,
+                object,
+            )
+        })
+        .collect();
+    records
+        .into_iter()
+        .map(
+            |(koid, type_name, created_at, creator, location, object)| LiveObject {
+                koid,
+                type_name,
+                name: object.map(|o| o.name()).unwrap_or_default(),
+                created_at,
+                creator,
+                location,
+            },
+        )
+        .collect()
+}
+
+/// 按类型名统计存活对象的数量
+pub fn live_object_counts() -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for object in live_objects() {
+        *counts.entry(object.type_name).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, alloc::sync::Arc};
+
+    #[test]
+    fn audit() {
+        let live = |koid| live_objects().into_iter().find(|o| o.koid == koid);
+        let object = DummyObject::new();
+        let info = live(object.id()).unwrap();
+        assert_eq!(info.type_name, "DummyObject");
+        // 创建位置为 `DummyObject::new`
+        assert!(info.location.file().ends_with("mod.rs"));
+
+        object.set_name("dummy");
+        let object: Arc<dyn KernelObject> = object;
+        let info = live(object.id()).unwrap();
+        assert_eq!(info.type_name, "DummyObject");
+        assert_eq!(info.name, "dummy");
+        assert!(live_object_counts()["DummyObject"] >= 1);
+
+        let koid = object.id();
+        drop(object);
+        assert!(live(koid).is_none());
+    }
+}
@@ -19,3 +19,40 @@ impl Handle {
     }
 }
 // ANCHOR_END: handle
+
+use super::{HandleBasicInfo, ObjectType};
+
+impl Handle {
+    /// Get information of the handle.
+    ///
+    /// The handle value is left `INVALID_HANDLE` to be filled by the caller.
+    pub fn get_handle_info(&self) -> HandleInfo {
+        HandleInfo {
+            handle: INVALID_HANDLE,
+            obj_type: self.object.obj_type(),
+            rights: self.rights,
+            unused: 0,
+        }
+    }
+
+    /// Get basic information of the handle, the `ZX_INFO_HANDLE_BASIC` topic.
+    pub fn get_handle_basic_info(&self) -> HandleBasicInfo {
+        HandleBasicInfo::new(
+            self.object.id(),
+            self.rights,
+            self.object.obj_type(),
+            self.object.related_koid(),
+        )
+    }
+}
+
+/// Information of a handle, the `zx_handle_info_t`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HandleInfo {
+    /// The handle value in the process.
+    pub handle: HandleValue,
+    obj_type: ObjectType,
+    rights: Rights,
+    unused: u32,
+}
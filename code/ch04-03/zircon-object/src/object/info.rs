@@ -0,0 +1,201 @@
+use {super::*, alloc::vec::Vec, core::mem::size_of, numeric_enum_macro::numeric_enum};
+
+numeric_enum! {
+    #[repr(u32)]
+    /// `object_get_info` 可查询的信息主题，即 `zx_object_info_topic_t`
+    #[allow(missing_docs)]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub enum Topic {
+        None = 0,
+        HandleValid = 1,
+        HandleBasic = 2,
+        Process = 3,
+        ProcessThreads = 4,
+        Vmar = 7,
+        JobChildren = 8,
+        JobProcesses = 9,
+        Thread = 10,
+        ThreadExceptionReport = 11,
+        TaskStats = 12,
+        ProcessMaps = 13,
+        ProcessVmos = 14,
+        ThreadStats = 15,
+        CpuStats = 16,
+        KmemStats = 17,
+        Resource = 18,
+        HandleCount = 19,
+        Bti = 20,
+        ProcessHandleStats = 21,
+        Socket = 22,
+        Vmo = 23,
+        Job = 24,
+        Timer = 25,
+        Stream = 26,
+        HandleTable = 27,
+        Msi = 28,
+        GuestStats = 29,
+        TaskRuntime = 30,
+        // zCore extensions
+        HandleQuota = 0x1000,
+        JobPolicy = 0x1001,
+    }
+}
+
+/// `object_get_info` 的查询结果
+///
+/// 由单条记录或者一组记录序列化而成，按原样拷贝给用户程序
+#[derive(Debug)]
+pub struct ObjectInfo {
+    data: Vec<u8>,
+    record_size: usize,
+    is_array: bool,
+}
+
+impl ObjectInfo {
+    /// 由单条记录创建
+    ///
+    /// 记录必须是 `#[repr(C)]` 且不含隐式填充字节
+    pub fn single<T>(record: T) -> Self {
+        ObjectInfo {
+            data: Vec::from(Self::bytes_of(core::slice::from_ref(&record))),
+            record_size: size_of::<T>(),
+            is_array: false,
+        }
+    }
+
+    /// 由一组记录创建
+    ///
+    /// 记录必须是 `#[repr(C)]` 且不含隐式填充字节
+    pub fn array<T>(records: &[T]) -> Self {
+        ObjectInfo {
+            data: Vec::from(Self::bytes_of(records)),
+            record_size: size_of::<T>(),
+            is_array: true,
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn bytes_of<T>(records: &[T]) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                records.as_ptr() as *const u8,
+                core::mem::size_of_val(records),
+            )
+        }
+    }
+
+    /// 是否为一组记录
+    pub fn is_array(&self) -> bool {
+        self.is_array
+    }
+
+    /// 单条记录的字节数
+    pub fn record_size(&self) -> usize {
+        self.record_size
+    }
+
+    /// 记录条数
+    pub fn count(&self) -> usize {
+        self.data.len().checked_div(self.record_size).unwrap_or(0)
+    }
+
+    /// 前 `count` 条记录的字节表示
+    pub fn as_bytes(&self, count: usize) -> &[u8] {
+        &self.data[..count.min(self.count()) * self.record_size]
+    }
+}
+
+/// 句柄的基本信息，即 `zx_info_handle_basic_t`
+#[repr(C)]
+#[derive(Debug)]
+pub struct HandleBasicInfo {
+    /// 对象 ID
+    pub koid: KoID,
+    /// 句柄权限
+    pub rights: Rights,
+    /// 对象类型
+    pub obj_type: ObjectType,
+    /// 关联对象 ID
+    pub related_koid: KoID,
+    reserved: u32,
+    padding: [u8; 4],
+}
+
+impl HandleBasicInfo {
+    /// 创建句柄基本信息
+    pub fn new(koid: KoID, rights: Rights, obj_type: ObjectType, related_koid: KoID) -> Self {
+        HandleBasicInfo {
+            koid,
+            rights,
+            obj_type,
+            related_koid,
+            reserved: 0,
+            padding: [0; 4],
+        }
+    }
+}
+
+/// 指向对象的句柄数量，即 `zx_info_handle_count_t`
+#[repr(C)]
+#[derive(Debug)]
+pub struct HandleCountInfo {
+    /// 句柄数量
+    pub handle_count: u32,
+}
+
+/// 任务的运行时间，即 `zx_info_task_runtime_t`
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct TaskRuntimeInfo {
+    /// 在 CPU 上运行的总时间（纳秒）
+    pub cpu_time: i64,
+    /// 就绪但等待调度的总时间（纳秒）
+    pub queue_time: i64,
+}
+
+/// 任务的句柄配额，`Topic::HandleQuota` 的结果
+#[repr(C)]
+#[derive(Debug)]
+pub struct HandleQuotaInfo {
+    /// 计入配额的句柄数量
+    pub handle_count: u64,
+    /// 句柄数量上限，`u64::MAX` 表示不限制
+    pub handle_limit: u64,
+}
+
+/// 作业的有效策略，`Topic::JobPolicy` 的结果
+#[repr(C)]
+#[derive(Debug)]
+pub struct JobPolicyInfo {
+    /// 已设置策略的条件集合，按 `PolicyCondition` 的位索引
+    pub conditions: u32,
+    /// 允许子作业覆盖策略的条件集合
+    pub override_allowed: u32,
+    /// 各条件的动作，每个条件占 4 位
+    pub actions: u64,
+    /// 定时器的最小 slack，单位为纳秒
+    pub timer_slack_min: i64,
+    /// 定时器默认的 slack 模式
+    pub timer_slack_mode: u32,
+    pub(crate) padding: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_info() {
+        let info = ObjectInfo::single(HandleCountInfo { handle_count: 3 });
+        assert!(!info.is_array());
+        assert_eq!(info.record_size(), 4);
+        assert_eq!(info.count(), 1);
+        assert_eq!(info.as_bytes(1), &3u32.to_ne_bytes());
+
+        let info = ObjectInfo::array::<KoID>(&[1, 2, 3]);
+        assert!(info.is_array());
+        assert_eq!(info.count(), 3);
+        assert_eq!(info.as_bytes(2).len(), 16);
+        assert_eq!(info.as_bytes(5).len(), 24);
+    }
+}
@@ -1,15 +1,32 @@
+use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use core::fmt::Debug;
+use core::future::Future;
+use core::pin::Pin;
 use core::sync::atomic::*;
+use core::task::{Context, Poll, Waker};
 use downcast_rs::{impl_downcast, DowncastSync};
 use spin::Mutex;
 
+#[cfg(feature = "audit")]
+pub mod audit;
 mod handle;
+mod info;
+mod object_type;
+mod property;
+mod registry;
 mod rights;
+mod signal;
 
 pub use self::handle::*;
+pub use self::info::*;
+pub use self::object_type::*;
+pub use self::property::*;
+pub use self::registry::*;
 pub use self::rights::*;
+pub use self::signal::*;
 pub use super::*;
 
 /// 内核对象公共接口
@@ -17,22 +34,53 @@ pub trait KernelObject: DowncastSync + Debug {
     /// 获取对象 ID
     fn id(&self) -> KoID;
     /// 获取对象类型名
-    fn type_name(&self) -> &str;
+    fn type_name(&self) -> &'static str;
+    /// 获取对象类型
+    fn obj_type(&self) -> ObjectType {
+        ObjectType::from_type_name(self.type_name())
+    }
     /// 获取对象名称
     fn name(&self) -> String;
     /// 设置对象名称
     fn set_name(&self, name: &str);
+    /// 获取对象当前的信号状态
+    fn signal(&self) -> Signal;
+    /// 置位信号
+    fn signal_set(&self, signal: Signal);
+    /// 清除信号
+    fn signal_clear(&self, signal: Signal);
+    /// 先清除 `clear` 再置位 `set`，只在信号发生变化时通知等待者
+    fn signal_change(&self, clear: Signal, set: Signal);
+    /// 添加一个信号回调函数
+    ///
+    /// 每当信号发生变化时调用，返回 `true` 表示完成使命，将被移除。
+    /// 返回的 ID 可用于提前移除该回调。
+    fn add_signal_callback(&self, callback: SignalHandler) -> SignalCallbackId;
+    /// 移除一个信号回调函数，回调已被移除时什么也不做
+    fn remove_signal_callback(&self, id: SignalCallbackId);
+    /// 获取进程句柄表中指向该对象的句柄数量
+    fn handle_count(&self) -> u32;
+    /// 句柄表中新增了一个指向该对象的句柄
+    fn inc_handle_count(&self);
+    /// 句柄表中移除了一个指向该对象的句柄
+    fn dec_handle_count(&self);
+    /// 标记对象已加入全局对象表，析构时需要将其移除
+    fn set_registered(&self);
+    /// 用户程序可以通过 `object_signal` 修改的信号
+    fn allowed_signals(&self) -> Signal {
+        Signal::USER_ALL
+    }
     /// 尝试获取对象伙伴
     ///
-    /// 当前该对象必须是 `Channel`
+    /// 当前该对象必须是 `Channel` 或者 `EventPair`
     fn peer(&self) -> ZxResult<Arc<dyn KernelObject>> {
         Err(ZxError::NOT_SUPPORTED)
     }
     /// 尝试获取关联对象 id，否则返回 0
     ///
-    /// 当前该对象必须是 `Channel` 或者 `Task`
+    /// 当前该对象必须是 `Channel`、`EventPair` 或者 `Task`
     ///
-    /// 如果该对象是 `Channel`, 将获取伙伴的 id
+    /// 如果该对象是 `Channel` 或者 `EventPair`, 将获取伙伴的 id
     ///
     /// 如果该对象是 `Task`, 将获取其父 `Task` 的 id
     fn related_koid(&self) -> KoID {
@@ -46,6 +94,24 @@ pub trait KernelObject: DowncastSync + Debug {
     fn get_child(&self, _id: KoID) -> ZxResult<Arc<dyn KernelObject>> {
         Err(ZxError::WRONG_TYPE)
     }
+    /// 获取对象关于 `topic` 的信息，用于实现 `object_get_info`
+    ///
+    /// 对象不支持该主题时返回 `WRONG_TYPE`
+    fn get_info(&self, _topic: Topic) -> ZxResult<ObjectInfo> {
+        Err(ZxError::WRONG_TYPE)
+    }
+    /// 获取对象属性 `property` 的值，用于实现 `object_get_property`
+    ///
+    /// `Name` 属性由 `name` 统一处理，对象不支持该属性时返回 `WRONG_TYPE`
+    fn get_property(&self, _property: Property) -> ZxResult<usize> {
+        Err(ZxError::WRONG_TYPE)
+    }
+    /// 设置对象属性 `property` 的值，用于实现 `object_set_property`
+    ///
+    /// `Name` 属性由 `set_name` 统一处理，对象不支持该属性时返回 `WRONG_TYPE`
+    fn set_property(&self, _property: Property, _value: usize) -> ZxResult {
+        Err(ZxError::WRONG_TYPE)
+    }
 }
 
 impl_downcast!(sync KernelObject);
@@ -53,10 +119,21 @@ impl_downcast!(sync KernelObject);
 /// 对象 ID 类型
 pub type KoID = u64;
 
+/// 信号回调函数类型，返回 `true` 表示不再需要该回调
+pub type SignalHandler = Box<dyn Fn(Signal) -> bool + Send + Sync>;
+
+/// 对象内保存的信号回调函数，以 `Arc` 共享以便在锁外调用
+type SharedSignalHandler = Arc<dyn Fn(Signal) -> bool + Send + Sync>;
+
+/// 信号回调函数的 ID，在同一个对象内唯一
+pub type SignalCallbackId = u64;
+
 /// 内核对象核心结构
 pub struct KObjectBase {
     /// 对象 ID
     pub id: KoID,
+    /// 是否已加入全局对象表
+    registered: AtomicBool,
     inner: Mutex<KObjectBaseInner>,
 }
 
@@ -64,6 +141,10 @@ pub struct KObjectBase {
 #[derive(Default)]
 struct KObjectBaseInner {
     name: String,
+    signal: Signal,
+    signal_callbacks: Vec<(SignalCallbackId, SharedSignalHandler)>,
+    next_callback_id: SignalCallbackId,
+    handle_count: u32,
 }
 
 impl Default for KObjectBase {
@@ -71,6 +152,7 @@ impl Default for KObjectBase {
     fn default() -> Self {
         KObjectBase {
             id: Self::new_koid(),
+            registered: AtomicBool::new(false),
             inner: Default::default(),
         }
     }
@@ -92,19 +174,285 @@ impl KObjectBase {
         self.inner.lock().name.clone()
     }
     /// 设置对象名称
+    ///
+    /// 超出 `MAX_NAME_LEN - 1` 字节的部分将被截断
     pub fn set_name(&self, name: &str) {
-        self.inner.lock().name = String::from(name);
+        self.inner.lock().name = String::from(truncate_name(name));
     }
 
     /// Create a kernel object base with `name`.
     pub fn with_name(name: &str) -> Self {
         KObjectBase {
             id: Self::new_koid(),
+            registered: AtomicBool::new(false),
             inner: Mutex::new(KObjectBaseInner {
-                name: String::from(name),
+                name: String::from(truncate_name(name)),
+                ..Default::default()
             }),
         }
     }
+
+    /// Create a kernel object base with initial `signal`.
+    pub fn with_signal(signal: Signal) -> Self {
+        KObjectBase {
+            id: Self::new_koid(),
+            registered: AtomicBool::new(false),
+            inner: Mutex::new(KObjectBaseInner {
+                signal,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// 获取信号状态
+    pub fn signal(&self) -> Signal {
+        self.inner.lock().signal
+    }
+
+    /// 先清除 `clear` 再置位 `set`
+    ///
+    /// 若信号发生变化，依次调用回调函数，并移除返回 `true` 的回调
+    pub fn signal_change(&self, clear: Signal, set: Signal) {
+        let (new_signal, callbacks) = {
+            let mut inner = self.inner.lock();
+            let old_signal = inner.signal;
+            inner.signal.remove(clear);
+            inner.signal.insert(set);
+            let new_signal = inner.signal;
+            if new_signal == old_signal || inner.signal_callbacks.is_empty() {
+                return;
+            }
+            (new_signal, inner.signal_callbacks.clone())
+        };
+        // 在锁外调用回调函数，使回调中可以再次访问该对象
+        let finished: Vec<_> = callbacks
+            .iter()
+            .filter(|(_, f)| f(new_signal))
+            .map(|&(id, _)| id)
+            .collect();
+        if !finished.is_empty() {
+            let mut inner = self.inner.lock();
+            inner
+                .signal_callbacks
+                .retain(|(id, _)| !finished.contains(id));
+        }
+    }
+
+    /// 置位信号
+    pub fn signal_set(&self, signal: Signal) {
+        self.signal_change(Signal::empty(), signal);
+    }
+
+    /// 清除信号
+    pub fn signal_clear(&self, signal: Signal) {
+        self.signal_change(signal, Signal::empty());
+    }
+
+    /// 添加一个信号回调函数，返回其 ID
+    pub fn add_signal_callback(&self, callback: SignalHandler) -> SignalCallbackId {
+        let callback: SharedSignalHandler = Arc::from(callback);
+        let (id, signal) = {
+            let mut inner = self.inner.lock();
+            let id = inner.next_callback_id;
+            inner.next_callback_id += 1;
+            inner.signal_callbacks.push((id, callback.clone()));
+            (id, inner.signal)
+        };
+        // 立即检查一次，以免在加锁之前信号已经到达而错过通知
+        if callback(signal) {
+            self.remove_signal_callback(id);
+        }
+        id
+    }
+
+    /// 移除一个信号回调函数
+    pub fn remove_signal_callback(&self, id: SignalCallbackId) {
+        let removed = {
+            let mut inner = self.inner.lock();
+            let index = inner.signal_callbacks.iter().position(|&(i, _)| i == id);
+            index.map(|index| inner.signal_callbacks.remove(index))
+        };
+        // 在锁外释放回调函数，使其析构时可以访问该对象
+        drop(removed);
+    }
+
+    /// 获取句柄数量
+    pub fn handle_count(&self) -> u32 {
+        self.inner.lock().handle_count
+    }
+
+    /// 句柄数量加一
+    pub fn inc_handle_count(&self) {
+        self.inner.lock().handle_count += 1;
+    }
+
+    /// 句柄数量减一
+    pub fn dec_handle_count(&self) {
+        self.inner.lock().handle_count -= 1;
+    }
+
+    /// 标记已加入全局对象表
+    pub fn set_registered(&self) {
+        self.registered.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for KObjectBase {
+    /// 从全局对象表中移除，从未注册的对象无需访问全局对象表
+    fn drop(&mut self) {
+        if *self.registered.get_mut() {
+            unregister_object(self.id);
+            #[cfg(feature = "audit")]
+            audit::on_drop(self.id);
+        }
+    }
+}
+
+/// 将名称截断到 `MAX_NAME_LEN - 1` 字节以内，不会截断多字节字符
+fn truncate_name(name: &str) -> &str {
+    let mut len = name.len().min(MAX_NAME_LEN - 1);
+    while !name.is_char_boundary(len) {
+        len -= 1;
+    }
+    &name[..len]
+}
+
+impl dyn KernelObject {
+    /// 异步等待直到 `signal` 中任意一个信号被置位，返回当时的信号状态
+    pub fn wait_signal(self: &Arc<Self>, signal: Signal) -> impl Future<Output = Signal> {
+        #[must_use = "wait_signal does nothing unless polled/`await`-ed"]
+        struct SignalFuture {
+            object: Arc<dyn KernelObject>,
+            signal: Signal,
+            waker: Arc<Mutex<Option<Waker>>>,
+            callback: Option<SignalCallbackId>,
+        }
+
+        impl SignalFuture {
+            fn remove_callback(&mut self) {
+                if let Some(id) = self.callback.take() {
+                    self.object.remove_signal_callback(id);
+                }
+            }
+        }
+
+        impl Drop for SignalFuture {
+            fn drop(&mut self) {
+                self.remove_callback();
+            }
+        }
+
+        impl Future for SignalFuture {
+            type Output = Signal;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+                // 每次轮询都更新 waker，回调总是唤醒最近一次轮询的任务
+                *self.waker.lock() = Some(cx.waker().clone());
+                let current_signal = self.object.signal();
+                if !(current_signal & self.signal).is_empty() {
+                    self.remove_callback();
+                    return Poll::Ready(current_signal);
+                }
+                if self.callback.is_none() {
+                    let callback = signal_waker_callback(self.signal, self.waker.clone());
+                    let id = self.object.add_signal_callback(callback);
+                    self.callback = Some(id);
+                }
+                Poll::Pending
+            }
+        }
+
+        SignalFuture {
+            object: self.clone(),
+            signal,
+            waker: Arc::default(),
+            callback: None,
+        }
+    }
+}
+
+/// 创建一个信号回调函数，`signal` 中任意一个信号被置位时唤醒 `waker`
+///
+/// 回调总是返回 `false`，在等待结束时由等待者移除，
+/// 以免信号被置位后又被清除时，再次等待没有回调可以唤醒。
+fn signal_waker_callback(signal: Signal, waker: Arc<Mutex<Option<Waker>>>) -> SignalHandler {
+    Box::new(move |s| {
+        if !(s & signal).is_empty() {
+            if let Some(waker) = waker.lock().take() {
+                waker.wake();
+            }
+        }
+        false
+    })
+}
+
+/// 异步等待多个对象的信号
+///
+/// 当任意一个对象的信号与期望的信号有交集时完成，返回所有对象当时的信号状态
+pub fn wait_signal_many(
+    targets: &[(Arc<dyn KernelObject>, Signal)],
+) -> impl Future<Output = Vec<Signal>> {
+    #[must_use = "wait_signal_many does nothing unless polled/`await`-ed"]
+    struct SignalManyFuture {
+        targets: Vec<(Arc<dyn KernelObject>, Signal)>,
+        waker: Arc<Mutex<Option<Waker>>>,
+        callbacks: Option<Vec<SignalCallbackId>>,
+    }
+
+    impl SignalManyFuture {
+        fn happened(&self, current_signals: &[Signal]) -> bool {
+            self.targets
+                .iter()
+                .zip(current_signals)
+                .any(|(&(_, desired), &current)| !(current & desired).is_empty())
+        }
+
+        fn remove_callbacks(&mut self) {
+            if let Some(callbacks) = self.callbacks.take() {
+                for ((object, _), id) in self.targets.iter().zip(callbacks) {
+                    object.remove_signal_callback(id);
+                }
+            }
+        }
+    }
+
+    impl Drop for SignalManyFuture {
+        fn drop(&mut self) {
+            self.remove_callbacks();
+        }
+    }
+
+    impl Future for SignalManyFuture {
+        type Output = Vec<Signal>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+            *self.waker.lock() = Some(cx.waker().clone());
+            let current_signals: Vec<_> =
+                self.targets.iter().map(|(obj, _)| obj.signal()).collect();
+            if self.happened(&current_signals) {
+                self.remove_callbacks();
+                return Poll::Ready(current_signals);
+            }
+            if self.callbacks.is_none() {
+                let callbacks = self
+                    .targets
+                    .iter()
+                    .map(|(object, signal)| {
+                        let callback = signal_waker_callback(*signal, self.waker.clone());
+                        object.add_signal_callback(callback)
+                    })
+                    .collect();
+                self.callbacks = Some(callbacks);
+            }
+            Poll::Pending
+        }
+    }
+
+    SignalManyFuture {
+        targets: Vec::from(targets),
+        waker: Arc::default(),
+        callbacks: None,
+    }
 }
 
 /// 为内核对象 struct 自动实现 `KernelObject` trait 的宏。
@@ -118,7 +466,7 @@ macro_rules! impl_kobject {
                 // 直接访问内部的 pub 属性
                 self.base.id
             }
-            fn type_name(&self) -> &str {
+            fn type_name(&self) -> &'static str {
                 // 用 stringify! 宏将输入转成字符串
                 stringify!($class)
             }
@@ -130,6 +478,36 @@ macro_rules! impl_kobject {
                 // 直接访问内部的 pub 方法
                 self.base.set_name(name)
             }
+            fn signal(&self) -> Signal {
+                self.base.signal()
+            }
+            fn signal_set(&self, signal: Signal) {
+                self.base.signal_set(signal);
+            }
+            fn signal_clear(&self, signal: Signal) {
+                self.base.signal_clear(signal);
+            }
+            fn signal_change(&self, clear: Signal, set: Signal) {
+                self.base.signal_change(clear, set);
+            }
+            fn add_signal_callback(&self, callback: SignalHandler) -> SignalCallbackId {
+                self.base.add_signal_callback(callback)
+            }
+            fn remove_signal_callback(&self, id: SignalCallbackId) {
+                self.base.remove_signal_callback(id);
+            }
+            fn handle_count(&self) -> u32 {
+                self.base.handle_count()
+            }
+            fn inc_handle_count(&self) {
+                self.base.inc_handle_count();
+            }
+            fn dec_handle_count(&self) {
+                self.base.dec_handle_count();
+            }
+            fn set_registered(&self) {
+                self.base.set_registered();
+            }
             // 可以传入任意数量的函数，覆盖 trait 的默认实现
             $( $fn )*
         }
@@ -161,9 +539,9 @@ impl_kobject!(DummyObject);
 impl DummyObject {
     /// 创建一个新 `DummyObject`
     pub fn new() -> Arc<Self> {
-        Arc::new(DummyObject {
+        register_object(Arc::new(DummyObject {
             base: KObjectBase::default(),
-        })
+        }))
     }
 }
 
@@ -183,3 +561,257 @@ fn impl_kobject() {
     );
     let _result: Arc<DummyObject> = object.downcast_arc::<DummyObject>().unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::sync::Barrier;
+    use core::time::Duration;
+
+    #[test]
+    fn signal() {
+        let object = DummyObject::new();
+        assert_eq!(object.signal(), Signal::empty());
+
+        object.signal_set(Signal::READABLE | Signal::WRITABLE);
+        assert_eq!(object.signal(), Signal::READABLE | Signal::WRITABLE);
+
+        object.signal_clear(Signal::READABLE);
+        assert_eq!(object.signal(), Signal::WRITABLE);
+
+        object.signal_change(Signal::WRITABLE, Signal::USER_SIGNAL_0);
+        assert_eq!(object.signal(), Signal::USER_SIGNAL_0);
+
+        let object = KObjectBase::with_signal(Signal::SIGNALED);
+        assert_eq!(object.signal(), Signal::SIGNALED);
+    }
+
+    #[test]
+    fn name_truncated() {
+        let object = DummyObject::new();
+        object.set_name(&"a".repeat(MAX_NAME_LEN));
+        assert_eq!(object.name().len(), MAX_NAME_LEN - 1);
+        // multi-byte characters are not split
+        object.set_name(&"中".repeat(MAX_NAME_LEN));
+        assert_eq!(object.name(), "中".repeat((MAX_NAME_LEN - 1) / 3));
+    }
+
+    #[test]
+    fn property() {
+        let object: Arc<dyn KernelObject> = DummyObject::new();
+        assert_eq!(
+            object.get_property(Property::VmoContentSize),
+            Err(ZxError::WRONG_TYPE)
+        );
+
+        let vmo: Arc<dyn KernelObject> = crate::vm::VmObject::new_paged(1);
+        vmo.set_property(Property::VmoContentSize, 100).unwrap();
+        assert_eq!(vmo.get_property(Property::VmoContentSize), Ok(100));
+        assert_eq!(
+            vmo.set_property(Property::VmoContentSize, 0x2000),
+            Err(ZxError::OUT_OF_RANGE)
+        );
+
+        let job: Arc<dyn KernelObject> = crate::task::Job::root();
+        assert_eq!(job.get_property(Property::JobKillOnOom), Ok(0));
+        job.set_property(Property::JobKillOnOom, 1).unwrap();
+        assert_eq!(job.get_property(Property::JobKillOnOom), Ok(1));
+        assert_eq!(
+            job.set_property(Property::JobKillOnOom, 2),
+            Err(ZxError::INVALID_ARGS)
+        );
+    }
+
+    #[test]
+    fn registry() {
+        // objects are registered on creation
+        let object: Arc<dyn KernelObject> = DummyObject::new();
+        let koid = object.id();
+        let found = get_object_by_koid(koid).unwrap();
+        assert!(Arc::ptr_eq(&found, &object));
+
+        drop(found);
+        drop(object);
+        assert!(get_object_by_koid(koid).is_none());
+    }
+
+    #[test]
+    fn obj_type() {
+        assert_eq!(DummyObject::new().obj_type(), ObjectType::None);
+        let (channel, _) = crate::ipc::Channel::create();
+        assert_eq!(channel.obj_type(), ObjectType::Channel);
+        let vmo = crate::vm::VmObject::new_paged(1);
+        assert_eq!(vmo.obj_type(), ObjectType::Vmo);
+    }
+
+    #[test]
+    fn signal_callback() {
+        let object = DummyObject::new();
+        let count = Arc::new(AtomicU32::new(0));
+        object.add_signal_callback(Box::new({
+            let count = count.clone();
+            move |s| {
+                count.fetch_add(1, Ordering::SeqCst);
+                s.contains(Signal::WRITABLE)
+            }
+        }));
+        // the callback is checked once when added
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // callback is not called if the signal does not change
+        object.signal_clear(Signal::READABLE);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        object.signal_set(Signal::READABLE);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        // callback returns true and is removed
+        object.signal_set(Signal::WRITABLE);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        object.signal_clear(Signal::WRITABLE);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn signal_callback_removed() {
+        let object = DummyObject::new();
+        let id = object.add_signal_callback(Box::new({
+            // callbacks are called outside the lock and can access the object
+            let object = object.clone();
+            move |s| {
+                if s.contains(Signal::READABLE) {
+                    object.signal_set(Signal::WRITABLE);
+                }
+                false
+            }
+        }));
+        object.signal_set(Signal::READABLE);
+        assert!(object.signal().contains(Signal::WRITABLE));
+
+        object.remove_signal_callback(id);
+        assert!(object.base.inner.lock().signal_callbacks.is_empty());
+        object.signal_clear(Signal::all());
+        object.signal_set(Signal::READABLE);
+        assert!(!object.signal().contains(Signal::WRITABLE));
+    }
+
+    #[async_std::test]
+    async fn wait_signal() {
+        let object = DummyObject::new();
+        let flag = Arc::new(AtomicU8::new(0));
+
+        // the signal is already set
+        object.signal_set(Signal::READABLE);
+        let object_dyn: Arc<dyn KernelObject> = object.clone();
+        let signal = object_dyn.wait_signal(Signal::READABLE).await;
+        assert_eq!(signal, Signal::READABLE);
+        object.signal_clear(Signal::READABLE);
+
+        let barrier = Arc::new(Barrier::new(2));
+        async_std::task::spawn({
+            let object = object.clone();
+            let flag = flag.clone();
+            let barrier = barrier.clone();
+            async move {
+                async_std::task::sleep(Duration::from_millis(20)).await;
+
+                // Assert an irrelevant signal to test the `false` branch of the callback for `READABLE`.
+                flag.store(1, Ordering::SeqCst);
+                object.signal_set(Signal::USER_SIGNAL_0);
+                object.signal_clear(Signal::USER_SIGNAL_0);
+                async_std::task::sleep(Duration::from_millis(10)).await;
+
+                flag.store(2, Ordering::SeqCst);
+                object.signal_set(Signal::READABLE);
+                barrier.wait().await;
+            }
+        });
+        let object: Arc<dyn KernelObject> = object;
+        let signal = object.wait_signal(Signal::READABLE).await;
+        assert_eq!(signal, Signal::READABLE);
+        assert_eq!(flag.load(Ordering::SeqCst), 2);
+        barrier.wait().await;
+    }
+
+    #[async_std::test]
+    async fn wait_signal_many() {
+        let objs = [DummyObject::new(), DummyObject::new()];
+        let flag = Arc::new(AtomicU8::new(0));
+
+        async_std::task::spawn({
+            let objs = objs.clone();
+            let flag = flag.clone();
+            async move {
+                async_std::task::sleep(Duration::from_millis(10)).await;
+                flag.store(1, Ordering::SeqCst);
+                objs[0].signal_set(Signal::READABLE);
+                async_std::task::sleep(Duration::from_millis(10)).await;
+                flag.store(2, Ordering::SeqCst);
+                objs[1].signal_set(Signal::WRITABLE);
+            }
+        });
+        let obj0: Arc<dyn KernelObject> = objs[0].clone();
+        let obj1: Arc<dyn KernelObject> = objs[1].clone();
+
+        // wait for the first object to be readable
+        let signals = super::wait_signal_many(&[
+            (obj0.clone(), Signal::READABLE),
+            (obj1.clone(), Signal::READABLE),
+        ])
+        .await;
+        assert_eq!(signals, [Signal::READABLE, Signal::empty()]);
+        assert_eq!(flag.load(Ordering::SeqCst), 1);
+
+        // wait for the second object to be writable
+        let signals = super::wait_signal_many(&[
+            (obj0.clone(), Signal::WRITABLE),
+            (obj1.clone(), Signal::WRITABLE),
+        ])
+        .await;
+        assert_eq!(signals, [Signal::READABLE, Signal::WRITABLE]);
+        assert_eq!(flag.load(Ordering::SeqCst), 2);
+    }
+
+    #[async_std::test]
+    async fn wait_signal_dropped() {
+        let object = DummyObject::new();
+        let object_dyn: Arc<dyn KernelObject> = object.clone();
+
+        // the callbacks of cancelled waits are removed
+        let wait = object_dyn.wait_signal(Signal::READABLE);
+        let ret = async_std::future::timeout(Duration::from_millis(10), wait).await;
+        assert!(ret.is_err());
+        let wait = super::wait_signal_many(&[(object_dyn.clone(), Signal::READABLE)]);
+        let ret = async_std::future::timeout(Duration::from_millis(10), wait).await;
+        assert!(ret.is_err());
+        assert!(object.base.inner.lock().signal_callbacks.is_empty());
+    }
+
+    #[async_std::test]
+    async fn wait_signal_set_and_cleared() {
+        let object = DummyObject::new();
+        let object_dyn: Arc<dyn KernelObject> = object.clone();
+        let wait = object_dyn.wait_signal(Signal::READABLE);
+        let wait_many = super::wait_signal_many(&[(object_dyn.clone(), Signal::READABLE)]);
+        futures::pin_mut!(wait, wait_many);
+        assert!(futures::poll!(&mut wait).is_pending());
+        assert!(futures::poll!(&mut wait_many).is_pending());
+
+        // the signal is set and cleared before the waits are polled again
+        object.signal_set(Signal::READABLE);
+        object.signal_clear(Signal::READABLE);
+        assert!(futures::poll!(&mut wait).is_pending());
+        assert!(futures::poll!(&mut wait_many).is_pending());
+
+        async_std::task::spawn({
+            let object = object.clone();
+            async move {
+                async_std::task::sleep(Duration::from_millis(10)).await;
+                object.signal_set(Signal::READABLE);
+            }
+        });
+        assert_eq!(wait.await, Signal::READABLE);
+        assert_eq!(wait_many.await, [Signal::READABLE]);
+        assert!(object.base.inner.lock().signal_callbacks.is_empty());
+    }
+}
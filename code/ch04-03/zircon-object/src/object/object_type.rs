@@ -0,0 +1,60 @@
+use numeric_enum_macro::numeric_enum;
+
+numeric_enum! {
+    #[repr(u32)]
+    /// 内核对象类型，即 `zx_obj_type_t`
+    #[allow(missing_docs)]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub enum ObjectType {
+        None = 0,
+        Process = 1,
+        Thread = 2,
+        Vmo = 3,
+        Channel = 4,
+        Event = 5,
+        Port = 6,
+        Interrupt = 9,
+        PciDevice = 11,
+        DebugLog = 12,
+        Socket = 14,
+        Resource = 15,
+        EventPair = 16,
+        Job = 17,
+        Vmar = 18,
+        Fifo = 19,
+        Guest = 20,
+        VCpu = 21,
+        Timer = 22,
+        Iommu = 23,
+        Bti = 24,
+        Profile = 25,
+        Pmt = 26,
+        SuspendToken = 27,
+        Pager = 28,
+        Exception = 29,
+        Clock = 30,
+        Stream = 31,
+    }
+}
+
+impl ObjectType {
+    /// 根据内核对象类型名获取对象类型，未知类型返回 `None`
+    pub fn from_type_name(name: &str) -> Self {
+        match name {
+            "Process" => ObjectType::Process,
+            "Thread" => ObjectType::Thread,
+            "VmObject" => ObjectType::Vmo,
+            "Channel" => ObjectType::Channel,
+            "Event" => ObjectType::Event,
+            "Port" => ObjectType::Port,
+            "DebugLog" => ObjectType::DebugLog,
+            "Resource" => ObjectType::Resource,
+            "EventPair" => ObjectType::EventPair,
+            "Job" => ObjectType::Job,
+            "VmAddressRegion" => ObjectType::Vmar,
+            "Timer" => ObjectType::Timer,
+            "SuspendToken" => ObjectType::SuspendToken,
+            _ => ObjectType::None,
+        }
+    }
+}
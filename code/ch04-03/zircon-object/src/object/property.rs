@@ -0,0 +1,35 @@
+use numeric_enum_macro::numeric_enum;
+
+/// 对象名称的最大长度，包括结尾的 `'\0'`，即 `ZX_MAX_NAME_LEN`
+pub const MAX_NAME_LEN: usize = 32;
+
+numeric_enum! {
+    #[repr(u32)]
+    /// `object_get_property` 和 `object_set_property` 可访问的对象属性
+    #[allow(missing_docs)]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub enum Property {
+        RegisterGs = 2,
+        Name = 3,
+        RegisterFs = 4,
+        ProcessDebugAddr = 5,
+        ProcessVdsoBaseAddress = 6,
+        JobKillOnOom = 15,
+        ExceptionState = 16,
+        VmoContentSize = 17,
+        // zCore extensions
+        // the handle limit of a process or job, `usize::MAX` for unlimited
+        HandleLimit = 0x1000,
+    }
+}
+
+impl Property {
+    /// 属性值在用户内存中的字节数
+    pub fn value_size(self) -> usize {
+        match self {
+            Property::Name => MAX_NAME_LEN,
+            Property::ExceptionState => core::mem::size_of::<u32>(),
+            _ => core::mem::size_of::<usize>(),
+        }
+    }
+}
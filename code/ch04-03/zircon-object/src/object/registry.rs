@@ -0,0 +1,41 @@
+use {
+    super::*,
+    alloc::collections::BTreeMap,
+    alloc::sync::{Arc, Weak},
+    lazy_static::lazy_static,
+    spin::Mutex,
+};
+
+lazy_static! {
+    /// 全局对象表，以 ID 为键保存对象的弱引用
+    static ref OBJECTS: Mutex<BTreeMap<KoID, Weak<dyn KernelObject>>> = Mutex::default();
+}
+
+/// 将新创建的对象加入全局对象表，使其可以通过 ID 查找
+///
+/// 由各内核对象的构造函数调用，对象在析构时自动移除。
+/// 启用 `audit` 特性时，同时记录对象的类型名和创建位置（即调用者）。
+#[track_caller]
+pub fn register_object<T: KernelObject>(object: Arc<T>) -> Arc<T> {
+    let weak: Weak<dyn KernelObject> = Arc::downgrade(&object) as Weak<T>;
+    #[cfg(feature = "audit")]
+    super::audit::on_create(
+        object.id(),
+        object.type_name(),
+        core::panic::Location::caller(),
+        weak.clone(),
+    );
+    OBJECTS.lock().insert(object.id(), weak);
+    object.set_registered();
+    object
+}
+
+/// 根据 ID 查找已注册且仍然存活的对象
+pub fn get_object_by_koid(koid: KoID) -> Option<Arc<dyn KernelObject>> {
+    OBJECTS.lock().get(&koid).and_then(Weak::upgrade)
+}
+
+/// 将对象从全局对象表中移除
+pub(super) fn unregister_object(koid: KoID) {
+    OBJECTS.lock().remove(&koid);
+}
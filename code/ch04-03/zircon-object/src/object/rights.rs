@@ -61,6 +61,18 @@ bitflags! {
 
         /// BASIC | WRITE | SIGNAL
         const DEFAULT_DEBUGLOG = Self::BASIC.bits | Self::WRITE.bits | Self::SIGNAL.bits;
+
+        /// (BASIC & !WAIT) | IO
+        const DEFAULT_PORT = (Self::BASIC.bits & !Self::WAIT.bits) | Self::IO.bits;
+
+        /// BASIC | SIGNAL
+        const DEFAULT_EVENT = Self::BASIC.bits | Self::SIGNAL.bits;
+
+        /// BASIC | SIGNAL | SIGNAL_PEER
+        const DEFAULT_EVENTPAIR = Self::BASIC.bits | Self::SIGNAL.bits | Self::SIGNAL_PEER.bits;
+
+        /// BASIC | WRITE | SIGNAL
+        const DEFAULT_TIMER = Self::BASIC.bits | Self::WRITE.bits | Self::SIGNAL.bits;
     }
 }
 // ANCHOR_END: rights
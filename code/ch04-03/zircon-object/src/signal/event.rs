@@ -0,0 +1,46 @@
+use {crate::object::*, alloc::sync::Arc};
+
+/// Signalable event for concurrent programming
+///
+/// ## SYNOPSIS
+///
+/// Events are user-signalable objects. The 8 signal bits reserved for
+/// userspace (`USER_SIGNAL_0` through `USER_SIGNAL_7`) may be set,
+/// cleared, and waited upon.
+pub struct Event {
+    base: KObjectBase,
+}
+
+impl_kobject!(Event
+    fn allowed_signals(&self) -> Signal {
+        Signal::USER_ALL | Signal::SIGNALED
+    }
+);
+
+impl Event {
+    /// Create a new `Event`.
+    pub fn new() -> Arc<Self> {
+        register_object(Arc::new(Event {
+            base: KObjectBase::default(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_signals() {
+        let event = Event::new();
+        assert!(Signal::verify_user_signal(
+            event.allowed_signals(),
+            (Signal::USER_SIGNAL_0 | Signal::SIGNALED).bits()
+        )
+        .is_ok());
+        assert_eq!(
+            Signal::verify_user_signal(event.allowed_signals(), Signal::READABLE.bits()),
+            Err(ZxError::INVALID_ARGS)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use {
+    crate::object::*,
+    alloc::sync::{Arc, Weak},
+};
+
+/// Mutually signalable pair of events for concurrent programming
+///
+/// ## SYNOPSIS
+///
+/// Event Pairs are linked pairs of user-signalable objects. The 8 signal
+/// bits reserved for userspace (`USER_SIGNAL_0` through `USER_SIGNAL_7`)
+/// may be set or cleared on the local or opposing endpoint of an Event Pair.
+///
+/// When one endpoint is destroyed, `PEER_CLOSED` is asserted on the other.
+pub struct EventPair {
+    base: KObjectBase,
+    peer: Weak<EventPair>,
+}
+
+impl_kobject!(EventPair
+    fn allowed_signals(&self) -> Signal {
+        Signal::USER_ALL | Signal::SIGNALED
+    }
+    fn peer(&self) -> ZxResult<Arc<dyn KernelObject>> {
+        let peer = self.peer.upgrade().ok_or(ZxError::PEER_CLOSED)?;
+        Ok(peer)
+    }
+    fn related_koid(&self) -> KoID {
+        self.peer.upgrade().map(|p| p.id()).unwrap_or(0)
+    }
+);
+
+impl EventPair {
+    /// Create a pair of event.
+    #[allow(unsafe_code)]
+    pub fn create() -> (Arc<Self>, Arc<Self>) {
+        let mut event0 = Arc::new(EventPair {
+            base: KObjectBase::default(),
+            peer: Weak::default(),
+        });
+        let event1 = Arc::new(EventPair {
+            base: KObjectBase::default(),
+            peer: Arc::downgrade(&event0),
+        });
+        // no other reference of `event0`
+        unsafe {
+            Arc::get_mut_unchecked(&mut event0).peer = Arc::downgrade(&event1);
+        }
+        (register_object(event0), register_object(event1))
+    }
+}
+
+impl Drop for EventPair {
+    fn drop(&mut self) {
+        if let Some(peer) = self.peer.upgrade() {
+            peer.base.signal_set(Signal::PEER_CLOSED);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_closed() {
+        let (event0, event1) = EventPair::create();
+        assert_eq!(event0.peer().unwrap().id(), event1.id());
+        assert_eq!(event0.related_koid(), event1.id());
+        assert_eq!(event1.related_koid(), event0.id());
+
+        drop(event1);
+        assert!(event0.signal().contains(Signal::PEER_CLOSED));
+        assert_eq!(event0.peer().unwrap_err(), ZxError::PEER_CLOSED);
+        assert_eq!(event0.related_koid(), 0);
+    }
+}
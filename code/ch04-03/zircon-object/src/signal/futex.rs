@@ -0,0 +1,356 @@
+use {
+    crate::object::*,
+    crate::task::Thread,
+    alloc::collections::VecDeque,
+    alloc::sync::Arc,
+    core::future::Future,
+    core::pin::Pin,
+    core::sync::atomic::{AtomicI32, Ordering},
+    core::task::{Context, Poll, Waker},
+    spin::Mutex,
+};
+
+/// A primitive for creating userspace synchronization tools
+///
+/// ## SYNOPSIS
+///
+/// A futex is a Fast Userspace muTEX. It is a low level synchronization
+/// primitive which is a building block for higher level APIs such as
+/// `pthread_mutex_t` and `pthread_cond_t`.
+///
+/// Futexes are designed to not enter the kernel or allocate kernel resources
+/// in the uncontested case.
+pub struct Futex {
+    base: KObjectBase,
+    value: &'static AtomicI32,
+    inner: Mutex<FutexInner>,
+}
+
+impl_kobject!(Futex);
+
+#[derive(Default)]
+struct FutexInner {
+    waiter_queue: VecDeque<Arc<Waiter>>,
+    owner: Option<Arc<Thread>>,
+}
+
+impl Futex {
+    /// Create a new futex on the user value at `value`.
+    pub fn new(value: &'static AtomicI32) -> Arc<Self> {
+        register_object(Arc::new(Futex {
+            base: KObjectBase::default(),
+            value,
+            inner: Mutex::default(),
+        }))
+    }
+
+    /// Wait on a futex asynchronously.
+    ///
+    /// See [`wait_with_owner`](#method.wait_with_owner) for details.
+    pub fn wait(self: &Arc<Self>, current_value: i32) -> FutexFuture {
+        self.wait_with_owner(current_value, None, None)
+    }
+
+    /// Wait on a futex asynchronously, and assign the ownership to `new_owner`.
+    ///
+    /// The value of the futex is checked atomically with enqueuing the waiter.
+    /// If it does not equal to `current_value`, the future returns `BAD_STATE`.
+    ///
+    /// The waiter is removed from the queue if the future is dropped before woken,
+    /// e.g. on timeout.
+    pub fn wait_with_owner(
+        self: &Arc<Self>,
+        current_value: i32,
+        thread: Option<Arc<Thread>>,
+        new_owner: Option<Arc<Thread>>,
+    ) -> FutexFuture {
+        FutexFuture {
+            waiter: Arc::new(Waiter {
+                thread,
+                inner: Mutex::new(WaiterInner {
+                    waker: None,
+                    woken: false,
+                    futex: self.clone(),
+                }),
+            }),
+            current_value,
+            new_owner,
+            registered: false,
+        }
+    }
+
+    /// Wake some number of threads waiting on a futex.
+    ///
+    /// The ownership of the futex is cleared.
+    /// Return the number of woken threads.
+    pub fn wake(&self, wake_count: usize) -> usize {
+        let mut inner = self.inner.lock();
+        inner.owner = None;
+        for i in 0..wake_count {
+            match inner.waiter_queue.pop_front() {
+                Some(waiter) => waiter.wake(),
+                None => return i,
+            }
+        }
+        wake_count
+    }
+
+    /// Wake one thread waiting on a futex, and assign the ownership to it.
+    ///
+    /// If there is no waiter, the ownership is cleared.
+    pub fn wake_single_owner(&self) {
+        let mut inner = self.inner.lock();
+        Self::wake_single_owner_locked(&mut inner);
+    }
+
+    fn wake_single_owner_locked(inner: &mut FutexInner) {
+        inner.owner = None;
+        if let Some(waiter) = inner.waiter_queue.pop_front() {
+            inner.owner = waiter.thread.clone();
+            waiter.wake();
+        }
+    }
+
+    /// Get the koid of the owner thread, or 0 if there is none.
+    pub fn owner(&self) -> KoID {
+        let inner = self.inner.lock();
+        inner.owner.as_ref().map(|t| t.id()).unwrap_or(0)
+    }
+
+    /// Wake some number of threads waiting on a futex,
+    /// and move more waiters to another wait queue.
+    ///
+    /// The ownership of this futex is cleared,
+    /// and the ownership of `requeue_futex` is assigned to `new_requeue_owner`.
+    pub fn requeue(
+        &self,
+        current_value: i32,
+        wake_count: usize,
+        requeue_count: usize,
+        requeue_futex: &Arc<Futex>,
+        new_requeue_owner: Option<Arc<Thread>>,
+    ) -> ZxResult {
+        self.requeue_internal(
+            current_value,
+            |inner| {
+                inner.owner = None;
+                for _ in 0..wake_count {
+                    match inner.waiter_queue.pop_front() {
+                        Some(waiter) => waiter.wake(),
+                        None => break,
+                    }
+                }
+            },
+            requeue_count,
+            requeue_futex,
+            new_requeue_owner,
+        )
+    }
+
+    /// Wake one thread waiting on a futex and assign the ownership to it,
+    /// then move more waiters to another wait queue.
+    pub fn requeue_single_owner(
+        &self,
+        current_value: i32,
+        requeue_count: usize,
+        requeue_futex: &Arc<Futex>,
+        new_requeue_owner: Option<Arc<Thread>>,
+    ) -> ZxResult {
+        self.requeue_internal(
+            current_value,
+            Self::wake_single_owner_locked,
+            requeue_count,
+            requeue_futex,
+            new_requeue_owner,
+        )
+    }
+
+    fn requeue_internal(
+        &self,
+        current_value: i32,
+        wake: impl FnOnce(&mut FutexInner),
+        requeue_count: usize,
+        requeue_futex: &Arc<Futex>,
+        new_requeue_owner: Option<Arc<Thread>>,
+    ) -> ZxResult {
+        if core::ptr::eq(self, requeue_futex.as_ref()) {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        // always lock the futex with lower address first to avoid deadlock
+        let (mut inner, mut requeue_inner) = if (self as *const Self) < Arc::as_ptr(requeue_futex) {
+            let inner = self.inner.lock();
+            (inner, requeue_futex.inner.lock())
+        } else {
+            let requeue_inner = requeue_futex.inner.lock();
+            (self.inner.lock(), requeue_inner)
+        };
+        if self.value.load(Ordering::SeqCst) != current_value {
+            return Err(ZxError::BAD_STATE);
+        }
+        wake(&mut inner);
+        for _ in 0..requeue_count {
+            match inner.waiter_queue.pop_front() {
+                Some(waiter) => {
+                    waiter.inner.lock().futex = requeue_futex.clone();
+                    requeue_inner.waiter_queue.push_back(waiter);
+                }
+                None => break,
+            }
+        }
+        requeue_inner.owner = new_requeue_owner;
+        Ok(())
+    }
+}
+
+struct Waiter {
+    /// The thread waiting on the futex.
+    thread: Option<Arc<Thread>>,
+    inner: Mutex<WaiterInner>,
+}
+
+struct WaiterInner {
+    waker: Option<Waker>,
+    woken: bool,
+    /// The futex whose queue the waiter is in, which changes on requeue.
+    futex: Arc<Futex>,
+}
+
+impl Waiter {
+    /// Wake up the waiter. Called with the futex locked.
+    fn wake(&self) {
+        let mut inner = self.inner.lock();
+        inner.woken = true;
+        if let Some(waker) = inner.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// The future returned by [`Futex::wait`](struct.Futex.html#method.wait).
+pub struct FutexFuture {
+    waiter: Arc<Waiter>,
+    current_value: i32,
+    new_owner: Option<Arc<Thread>>,
+    registered: bool,
+}
+
+impl Future for FutexFuture {
+    type Output = ZxResult;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.registered {
+            let mut inner = self.waiter.inner.lock();
+            if inner.woken {
+                return Poll::Ready(Ok(()));
+            }
+            inner.waker.replace(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let futex = self.waiter.inner.lock().futex.clone();
+        let mut futex_inner = futex.inner.lock();
+        if futex.value.load(Ordering::SeqCst) != self.current_value {
+            return Poll::Ready(Err(ZxError::BAD_STATE));
+        }
+        futex_inner.owner = self.new_owner.take();
+        self.waiter.inner.lock().waker.replace(cx.waker().clone());
+        futex_inner.waiter_queue.push_back(self.waiter.clone());
+        drop(futex_inner);
+        self.registered = true;
+        Poll::Pending
+    }
+}
+
+impl Drop for FutexFuture {
+    /// Remove the waiter from the queue if it has not been woken.
+    fn drop(&mut self) {
+        if !self.registered {
+            return;
+        }
+        loop {
+            let futex = self.waiter.inner.lock().futex.clone();
+            let mut futex_inner = futex.inner.lock();
+            let inner = self.waiter.inner.lock();
+            // the waiter has been requeued to another futex, retry
+            if !Arc::ptr_eq(&inner.futex, &futex) {
+                continue;
+            }
+            if !inner.woken {
+                futex_inner
+                    .waiter_queue
+                    .retain(|w| !Arc::ptr_eq(w, &self.waiter));
+            }
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{Job, Process};
+    use alloc::boxed::Box;
+    use futures::future::{select, Either};
+
+    fn new_value(value: i32) -> &'static AtomicI32 {
+        Box::leak(Box::new(AtomicI32::new(value)))
+    }
+
+    #[async_std::test]
+    async fn wait_wake() {
+        let futex = Futex::new(new_value(1));
+        assert_eq!(futex.wait(0).await, Err(ZxError::BAD_STATE));
+
+        let mut future = futex.wait(1);
+        assert!(futures::poll!(&mut future).is_pending());
+        assert_eq!(futex.wake(2), 1);
+        assert_eq!(future.await, Ok(()));
+        assert_eq!(futex.wake(1), 0);
+    }
+
+    #[async_std::test]
+    async fn cancel() {
+        let futex = Futex::new(new_value(0));
+        let future = futex.wait(0);
+        let timeout = async_std::task::sleep(core::time::Duration::from_millis(10));
+        match select(future, Box::pin(timeout)).await {
+            Either::Right(_) => {}
+            Either::Left(_) => panic!("futex should not be woken"),
+        }
+        // the dropped waiter should not be counted
+        assert_eq!(futex.wake(1), 0);
+    }
+
+    #[async_std::test]
+    async fn requeue_owner() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").unwrap();
+        let thread0 = Thread::create(&proc, "thread0").unwrap();
+        let thread1 = Thread::create(&proc, "thread1").unwrap();
+        let futex = Futex::new(new_value(0));
+        let requeue_futex = Futex::new(new_value(0));
+
+        let mut future0 = futex.wait_with_owner(0, Some(thread0.clone()), None);
+        let mut future1 = futex.wait_with_owner(0, Some(thread1.clone()), Some(thread0.clone()));
+        assert!(futures::poll!(&mut future0).is_pending());
+        assert!(futures::poll!(&mut future1).is_pending());
+        assert_eq!(futex.owner(), thread0.id());
+
+        assert_eq!(
+            futex.requeue_single_owner(1, 1, &requeue_futex, None),
+            Err(ZxError::BAD_STATE)
+        );
+        futex
+            .requeue_single_owner(0, 1, &requeue_futex, Some(thread0.clone()))
+            .unwrap();
+        assert_eq!(future0.await, Ok(()));
+        assert_eq!(futex.owner(), thread0.id());
+        assert_eq!(requeue_futex.owner(), thread0.id());
+
+        // the requeued waiter is woken by the new futex
+        assert_eq!(futex.wake(1), 0);
+        assert_eq!(futex.owner(), 0);
+        requeue_futex.wake_single_owner();
+        assert_eq!(future1.await, Ok(()));
+        assert_eq!(requeue_futex.owner(), thread1.id());
+    }
+}
@@ -0,0 +1,8 @@
+//! Objects for signaling and waiting.
+
+mod event;
+mod eventpair;
+mod futex;
+mod timer;
+
+pub use self::{event::*, eventpair::*, futex::*, timer::*};
@@ -0,0 +1,137 @@
+use {
+    crate::object::*, alloc::boxed::Box, alloc::sync::Arc, core::time::Duration,
+    numeric_enum_macro::numeric_enum, spin::Mutex,
+};
+
+/// An object that may be signaled at some point in the future
+///
+/// ## SYNOPSIS
+///
+/// A timer is used to wait until a specified point in time has occurred
+/// or the timer has been canceled.
+pub struct Timer {
+    base: KObjectBase,
+    slack: Slack,
+    inner: Mutex<TimerInner>,
+}
+
+impl_kobject!(Timer);
+
+#[derive(Default)]
+struct TimerInner {
+    deadline: Option<Duration>,
+    /// Bumped by every `set`, so that HAL callbacks of earlier ones are ignored.
+    generation: u64,
+}
+
+numeric_enum! {
+    #[repr(u32)]
+    /// How the timer may fire within its slack.
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    pub enum Slack {
+        /// Fire in `[deadline - slack, deadline + slack]`.
+        Center = 0,
+        /// Fire in `[deadline - slack, deadline]`.
+        Early = 1,
+        /// Fire in `[deadline, deadline + slack]`.
+        Late = 2,
+    }
+}
+
+impl Timer {
+    /// Create a new `Timer`.
+    pub fn create(slack: Slack) -> Arc<Self> {
+        register_object(Arc::new(Timer {
+            base: KObjectBase::default(),
+            slack,
+            inner: Mutex::default(),
+        }))
+    }
+
+    /// Get the slack policy of the timer.
+    pub fn slack(&self) -> Slack {
+        self.slack
+    }
+
+    /// Starts a one-shot timer that will fire when `deadline` passes.
+    ///
+    /// If a previous call to `set` was pending, the previous timer is canceled
+    /// and `TIMER_SIGNALED` is de-asserted as needed.
+    ///
+    /// The `slack` is accepted but currently the timer always fires at `deadline`,
+    /// which is valid under every slack policy.
+    pub fn set(self: &Arc<Self>, deadline: Duration, _slack: Duration) {
+        let generation = {
+            let mut inner = self.inner.lock();
+            inner.deadline = Some(deadline);
+            inner.generation += 1;
+            inner.generation
+        };
+        // signal callbacks and the HAL timer are called without the lock
+        self.base.signal_clear(Signal::TIMER_SIGNALED);
+        let me = Arc::downgrade(self);
+        kernel_hal::timer_set(
+            deadline,
+            Box::new(move |now| {
+                if let Some(timer) = me.upgrade() {
+                    timer.touch(now, generation);
+                }
+            }),
+        );
+    }
+
+    /// Cancel the pending timer started by `set`.
+    pub fn cancel(&self) {
+        self.inner.lock().deadline = None;
+        self.base.signal_clear(Signal::TIMER_SIGNALED);
+    }
+
+    /// Called by HAL timer armed by the `set` of `generation`.
+    /// Fire if the current deadline has passed.
+    ///
+    /// Callbacks of canceled or replaced deadlines are ignored here.
+    fn touch(&self, now: Duration, generation: u64) {
+        {
+            let mut inner = self.inner.lock();
+            match inner.deadline {
+                Some(deadline) if inner.generation == generation && now >= deadline => {
+                    inner.deadline = None;
+                }
+                _ => return,
+            }
+        }
+        self.base.signal_set(Signal::TIMER_SIGNALED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kernel_hal::timer_now;
+
+    #[async_std::test]
+    async fn set() {
+        kernel_hal_unix::init();
+        let timer = Timer::create(Slack::Center);
+        assert!(!timer.signal().contains(Signal::TIMER_SIGNALED));
+
+        timer.set(timer_now() + Duration::from_millis(10), Duration::default());
+        let object = timer.clone() as Arc<dyn KernelObject>;
+        object.wait_signal(Signal::TIMER_SIGNALED).await;
+        assert!(timer.signal().contains(Signal::TIMER_SIGNALED));
+
+        // setting a new deadline clears the signal
+        timer.set(timer_now() + Duration::from_secs(1000), Duration::default());
+        assert!(!timer.signal().contains(Signal::TIMER_SIGNALED));
+    }
+
+    #[async_std::test]
+    async fn cancel() {
+        kernel_hal_unix::init();
+        let timer = Timer::create(Slack::Late);
+        timer.set(timer_now() + Duration::from_millis(10), Duration::default());
+        timer.cancel();
+        async_std::task::sleep(Duration::from_millis(30)).await;
+        assert!(!timer.signal().contains(Signal::TIMER_SIGNALED));
+    }
+}
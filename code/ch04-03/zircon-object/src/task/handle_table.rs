@@ -0,0 +1,371 @@
+use {
+    crate::{error::*, object::*},
+    alloc::{collections::VecDeque, sync::Arc, vec::Vec},
+    core::sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The low bits which are always set in a valid handle value.
+const HANDLE_FIXED_BITS: u32 = 0b11;
+/// The number of bits encoding the slot index.
+const SLOT_BITS: u32 = 18;
+/// The number of bits encoding the generation of the slot.
+const GENERATION_BITS: u32 = 32 - 2 - SLOT_BITS;
+
+/// The maximum number of slots in a handle table.
+const MAX_HANDLE_SLOTS: usize = 1 << SLOT_BITS;
+
+const MAX_GENERATION: u32 = (1 << GENERATION_BITS) - 1;
+
+/// A handle table of a process.
+///
+/// Handles are stored in slots. A handle value encodes the slot index and
+/// the generation of the slot, which is bumped every time a handle is removed.
+/// So a stale value referring to a reused slot, or a forged one, is always rejected.
+///
+/// Freed slots are reused in FIFO order, and a slot is retired instead of
+/// reused when its generation would wrap around.
+///
+/// Every handle in the table is charged to each of the `quotas`,
+/// which are the quota of the process and those of its ancestor jobs.
+#[derive(Default)]
+pub(super) struct HandleTable {
+    slots: Vec<Slot>,
+    free: VecDeque<u32>,
+    len: usize,
+    quotas: Vec<Arc<HandleQuota>>,
+}
+
+#[derive(Default)]
+struct Slot {
+    generation: u32,
+    handle: Option<Handle>,
+    /// The generation of the last removed handle and where it was removed.
+    #[cfg(feature = "debug-handle")]
+    closed: Option<(u32, &'static core::panic::Location<'static>)>,
+}
+
+fn encode(index: u32, generation: u32) -> HandleValue {
+    (generation << (SLOT_BITS + 2)) | (index << 2) | HANDLE_FIXED_BITS
+}
+
+fn decode(value: HandleValue) -> Option<(usize, u32)> {
+    if value & HANDLE_FIXED_BITS != HANDLE_FIXED_BITS {
+        return None;
+    }
+    let index = (value >> 2) & (MAX_HANDLE_SLOTS as u32 - 1);
+    let generation = value >> (SLOT_BITS + 2);
+    Some((index as usize, generation))
+}
+
+impl HandleTable {
+    /// Create an empty table charging handles to `quotas`.
+    pub fn new(quotas: Vec<Arc<HandleQuota>>) -> Self {
+        HandleTable {
+            slots: Vec::new(),
+            free: VecDeque::new(),
+            len: 0,
+            quotas,
+        }
+    }
+
+    /// The number of handles in the table.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The number of handles which can still be inserted.
+    pub fn available(&self) -> usize {
+        self.free.len() + (MAX_HANDLE_SLOTS - self.slots.len())
+    }
+
+    /// Insert a handle, return its value.
+    ///
+    /// Return `NO_RESOURCES` if all slots are in use or retired,
+    /// or `INTERNAL_QUOTA_EXCEEDED` if `enforce_quota` and a quota is exceeded.
+    pub fn insert(&mut self, handle: Handle, enforce_quota: bool) -> ZxResult<HandleValue> {
+        if self.available() == 0 {
+            return Err(ZxError::NO_RESOURCES);
+        }
+        self.charge(1, enforce_quota)?;
+        Ok(self.insert_charged(handle))
+    }
+
+    /// Insert all handles, or none of them if there is no room for all.
+    pub fn insert_all(
+        &mut self,
+        handles: Vec<Handle>,
+        enforce_quota: bool,
+    ) -> ZxResult<Vec<HandleValue>> {
+        if self.available() < handles.len() {
+            return Err(ZxError::NO_RESOURCES);
+        }
+        self.charge(handles.len(), enforce_quota)?;
+        Ok(handles
+            .into_iter()
+            .map(|handle| self.insert_charged(handle))
+            .collect())
+    }
+
+    fn insert_charged(&mut self, handle: Handle) -> HandleValue {
+        let index = match self.free.pop_front() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot::default());
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.handle = Some(handle);
+        self.len += 1;
+        encode(index, slot.generation)
+    }
+
+    /// Whether `count` handles can be inserted, without exceeding any quota
+    /// if `enforce_quota`.
+    pub fn has_room(&self, count: usize, enforce_quota: bool) -> bool {
+        self.available() >= count
+            && (!enforce_quota || self.quotas.iter().all(|quota| quota.has_room(count)))
+    }
+
+    /// Charge `count` handles to all quotas.
+    fn charge(&self, count: usize, enforce: bool) -> ZxResult {
+        for (i, quota) in self.quotas.iter().enumerate() {
+            if let Err(err) = quota.charge(count, enforce) {
+                for quota in &self.quotas[..i] {
+                    quota.uncharge(count);
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn uncharge(&self, count: usize) {
+        for quota in self.quotas.iter() {
+            quota.uncharge(count);
+        }
+    }
+
+    /// Get the handle of `value`.
+    pub fn get(&self, value: HandleValue) -> ZxResult<&Handle> {
+        let (index, generation) = decode(value).ok_or(ZxError::BAD_HANDLE)?;
+        let slot = self.slots.get(index).ok_or(ZxError::BAD_HANDLE)?;
+        match &slot.handle {
+            Some(handle) if slot.generation == generation => Ok(handle),
+            _ => {
+                #[cfg(feature = "debug-handle")]
+                Self::report_stale(slot, value, generation);
+                Err(ZxError::BAD_HANDLE)
+            }
+        }
+    }
+
+    /// Remove the handle of `value`.
+    #[track_caller]
+    pub fn remove(&mut self, value: HandleValue) -> ZxResult<Handle> {
+        self.get(value)?;
+        Ok(self.remove_at(decode(value).unwrap().0))
+    }
+
+    /// Remove all handles.
+    #[track_caller]
+    pub fn clear(&mut self) -> Vec<Handle> {
+        let mut handles = Vec::with_capacity(self.len);
+        for index in 0..self.slots.len() {
+            if self.slots[index].handle.is_some() {
+                handles.push(self.remove_at(index));
+            }
+        }
+        handles
+    }
+
+    /// Remove the handle in an occupied slot, and bump or retire the slot.
+    #[track_caller]
+    fn remove_at(&mut self, index: usize) -> Handle {
+        self.uncharge(1);
+        let slot = &mut self.slots[index];
+        let handle = slot.handle.take().unwrap();
+        #[cfg(feature = "debug-handle")]
+        {
+            slot.closed = Some((slot.generation, core::panic::Location::caller()));
+        }
+        self.len -= 1;
+        if slot.generation < MAX_GENERATION {
+            slot.generation += 1;
+            self.free.push_back(index as u32);
+        }
+        handle
+    }
+
+    /// Log where the handle was closed when a stale value is used.
+    #[cfg(feature = "debug-handle")]
+    fn report_stale(slot: &Slot, value: HandleValue, generation: u32) {
+        match slot.closed {
+            Some((closed, location)) if closed == generation => {
+                warn!("use of closed handle {:#x}, closed at {}", value, location);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Drop for HandleTable {
+    fn drop(&mut self) {
+        self.uncharge(self.len);
+    }
+}
+
+/// A limit on the number of handles held by a process, or by all processes in a job.
+pub(super) struct HandleQuota {
+    count: AtomicUsize,
+    limit: AtomicUsize,
+}
+
+impl Default for HandleQuota {
+    fn default() -> Self {
+        HandleQuota {
+            count: AtomicUsize::new(0),
+            limit: AtomicUsize::new(usize::MAX),
+        }
+    }
+}
+
+impl HandleQuota {
+    /// Set the maximum number of handles, `None` for unlimited.
+    ///
+    /// Handles already charged are not affected.
+    pub fn set_limit(&self, limit: Option<usize>) {
+        self.limit
+            .store(limit.unwrap_or(usize::MAX), Ordering::SeqCst);
+    }
+
+    /// Get the maximum number of handles, `usize::MAX` for unlimited.
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of charged handles and the limit.
+    pub fn info(&self) -> HandleQuotaInfo {
+        HandleQuotaInfo {
+            handle_count: self.count.load(Ordering::SeqCst) as u64,
+            handle_limit: self.limit.load(Ordering::SeqCst) as u64,
+        }
+    }
+
+    fn has_room(&self, count: usize) -> bool {
+        self.count.load(Ordering::SeqCst) + count <= self.limit.load(Ordering::SeqCst)
+    }
+
+    fn charge(&self, count: usize, enforce: bool) -> ZxResult {
+        let old = self.count.fetch_add(count, Ordering::SeqCst);
+        if enforce && old + count > self.limit.load(Ordering::SeqCst) {
+            self.count.fetch_sub(count, Ordering::SeqCst);
+            return Err(ZxError::INTERNAL_QUOTA_EXCEEDED);
+        }
+        Ok(())
+    }
+
+    fn uncharge(&self, count: usize) {
+        self.count.fetch_sub(count, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_value() {
+        let mut table = HandleTable::default();
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        let value = table.insert(handle.clone(), true).unwrap();
+        assert_eq!(value & HANDLE_FIXED_BITS, HANDLE_FIXED_BITS);
+        assert!(table.get(value).is_ok());
+
+        table.remove(value).unwrap();
+        assert_eq!(table.get(value).err(), Some(ZxError::BAD_HANDLE));
+        assert_eq!(table.remove(value).err(), Some(ZxError::BAD_HANDLE));
+
+        // the slot is reused with a new generation
+        let new_value = table.insert(handle, true).unwrap();
+        assert_ne!(new_value, value);
+        assert_eq!(decode(new_value).unwrap().0, decode(value).unwrap().0);
+        assert_eq!(table.get(value).err(), Some(ZxError::BAD_HANDLE));
+        assert!(table.get(new_value).is_ok());
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn forged_value() {
+        let mut table = HandleTable::default();
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        let value = table.insert(handle, true).unwrap();
+        assert_eq!(table.get(value & !1).err(), Some(ZxError::BAD_HANDLE));
+        assert_eq!(table.get(value + 4).err(), Some(ZxError::BAD_HANDLE));
+        assert_eq!(
+            table.get(value + (1 << (SLOT_BITS + 2))).err(),
+            Some(ZxError::BAD_HANDLE)
+        );
+        assert_eq!(table.get(INVALID_HANDLE).err(), Some(ZxError::BAD_HANDLE));
+    }
+
+    #[test]
+    fn retire_slot() {
+        let mut table = HandleTable::default();
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        for _ in 0..MAX_GENERATION {
+            let value = table.insert(handle.clone(), true).unwrap();
+            table.remove(value).unwrap();
+        }
+        // the only slot has been retired after its last generation
+        let value = table.insert(handle.clone(), true).unwrap();
+        table.remove(value).unwrap();
+        assert_eq!(decode(table.insert(handle, true).unwrap()).unwrap().0, 1);
+        assert_eq!(table.available(), MAX_HANDLE_SLOTS - 2);
+    }
+
+    #[test]
+    fn quota() {
+        let quota = Arc::new(HandleQuota::default());
+        let parent_quota = Arc::new(HandleQuota::default());
+        quota.set_limit(Some(2));
+        parent_quota.set_limit(Some(3));
+        let mut table = HandleTable::new(vec![quota.clone(), parent_quota.clone()]);
+        let mut other = HandleTable::new(vec![parent_quota.clone()]);
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+
+        let value = table.insert(handle.clone(), true).unwrap();
+        other.insert(handle.clone(), true).unwrap();
+        assert_eq!(
+            table
+                .insert_all(vec![handle.clone(), handle.clone()], true)
+                .err(),
+            Some(ZxError::INTERNAL_QUOTA_EXCEEDED)
+        );
+        table.insert(handle.clone(), true).unwrap();
+        assert_eq!(
+            other.insert(handle.clone(), true).err(),
+            Some(ZxError::INTERNAL_QUOTA_EXCEEDED)
+        );
+
+        // exceeding the parent quota rolls back the charge of the process
+        quota.set_limit(Some(5));
+        assert_eq!(
+            table.insert(handle.clone(), true).err(),
+            Some(ZxError::INTERNAL_QUOTA_EXCEEDED)
+        );
+        assert_eq!(quota.info().handle_count, 2);
+        assert_eq!(parent_quota.info().handle_count, 3);
+
+        table.remove(value).unwrap();
+        other.insert(handle.clone(), true).unwrap();
+        drop(table);
+        assert_eq!(quota.info().handle_count, 0);
+        assert_eq!(parent_quota.info().handle_count, 2);
+
+        // an unenforced charge may exceed the limit
+        parent_quota.set_limit(Some(1));
+        other.insert(handle, false).unwrap();
+        assert_eq!(parent_quota.info().handle_count, 3);
+    }
+}
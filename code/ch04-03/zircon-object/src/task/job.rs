@@ -1,4 +1,5 @@
 use {
+    super::handle_table::HandleQuota,
     super::job_policy::*,
     super::process::Process,
     super::*,
@@ -16,6 +17,7 @@ pub struct Job {
     base: KObjectBase,
     parent: Option<Arc<Job>>,
     parent_policy: JobPolicy,
+    handle_quota: Arc<HandleQuota>,
     inner: Mutex<JobInner>,
 }
 
@@ -33,6 +35,37 @@ impl_kobject!(Job
     fn related_koid(&self) -> KoID {
         self.parent.as_ref().map(|p| p.id()).unwrap_or(0)
     }
+    fn get_info(&self, topic: Topic) -> ZxResult<ObjectInfo> {
+        match topic {
+            Topic::JobChildren => Ok(ObjectInfo::array(&self.children_ids())),
+            Topic::JobProcesses => Ok(ObjectInfo::array(&self.process_ids())),
+            Topic::TaskRuntime => Ok(ObjectInfo::single(self.get_task_runtime())),
+            Topic::HandleQuota => Ok(ObjectInfo::single(self.handle_quota.info())),
+            Topic::JobPolicy => Ok(ObjectInfo::single(self.policy().info())),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+    fn get_property(&self, property: Property) -> ZxResult<usize> {
+        match property {
+            Property::JobKillOnOom => Ok(self.get_kill_on_oom() as usize),
+            Property::HandleLimit => Ok(self.handle_quota.limit()),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+    fn set_property(&self, property: Property, value: usize) -> ZxResult {
+        match property {
+            Property::JobKillOnOom => match value {
+                0 => self.set_kill_on_oom(false),
+                1 => self.set_kill_on_oom(true),
+                _ => return Err(ZxError::INVALID_ARGS),
+            },
+            Property::HandleLimit => {
+                self.set_handle_limit(Some(value).filter(|&limit| limit != usize::MAX))
+            }
+            _ => return Err(ZxError::WRONG_TYPE),
+        }
+        Ok(())
+    }
 );
 
 #[derive(Default)]
@@ -42,18 +75,21 @@ struct JobInner {
     processes: Vec<Arc<Process>>,
     // if the job is killed, no more child creation should works
     killed: bool,
+    // whether the job should be killed when the system runs out of memory
+    kill_on_oom: bool,
     self_ref: Weak<Job>,
 }
 
 impl Job {
     /// Create the root job.
     pub fn root() -> Arc<Self> {
-        let job = Arc::new(Job {
+        let job = register_object(Arc::new(Job {
             base: KObjectBase::new(),
             parent: None,
             parent_policy: JobPolicy::default(),
+            handle_quota: Arc::default(),
             inner: Mutex::new(JobInner::default()),
-        });
+        }));
         job.inner.lock().self_ref = Arc::downgrade(&job);
         job
     }
@@ -64,12 +100,13 @@ impl Job {
         if inner.killed {
             return Err(ZxError::BAD_STATE);
         }
-        let child = Arc::new(Job {
+        let child = register_object(Arc::new(Job {
             base: KObjectBase::new(),
             parent: Some(self.clone()),
             parent_policy: inner.policy.merge(&self.parent_policy),
+            handle_quota: Arc::default(),
             inner: Mutex::new(JobInner::default()),
-        });
+        }));
         let child_weak = Arc::downgrade(&child);
         child.inner.lock().self_ref = child_weak.clone();
         inner.children.push(child_weak);
@@ -90,6 +127,23 @@ impl Job {
         self.inner.lock().policy.merge(&self.parent_policy)
     }
 
+    /// Set the maximum number of handles held by all processes in the job
+    /// and its descendants, `None` for unlimited.
+    pub fn set_handle_limit(&self, limit: Option<usize>) {
+        self.handle_quota.set_limit(limit);
+    }
+
+    /// Get the handle quotas of the job and its ancestors, from the nearest.
+    pub(super) fn handle_quotas(&self) -> Vec<Arc<HandleQuota>> {
+        let mut quotas = Vec::new();
+        let mut job = Some(self);
+        while let Some(j) = job {
+            quotas.push(j.handle_quota.clone());
+            job = j.parent.as_deref();
+        }
+        quotas
+    }
+
     /// Get the parent job.
     pub fn parent(&self) -> Option<Arc<Self>> {
         self.parent.clone()
@@ -102,25 +156,66 @@ impl Job {
     ///
     /// After this call succeeds any new child process or child job will have
     /// the new effective policy applied to it.
+    ///
+    /// The policies can not be overridden by child jobs.
     pub fn set_policy_basic(
         &self,
         options: SetPolicyOptions,
         policies: &[BasicPolicy],
+    ) -> ZxResult {
+        let policies: Vec<BasicPolicyV2> = policies.iter().map(|&p| p.into()).collect();
+        self.set_policy_basic_v2(options, &policies)
+    }
+
+    /// Sets one or more basic policies with override flags to an empty job.
+    ///
+    /// A policy conflicts with the parent's one if the parent has set a
+    /// different action for the condition and does not allow overriding it.
+    /// Conflicting policies fail the call if `options` is `Absolute`,
+    /// or are ignored if it is `Relative`.
+    pub fn set_policy_basic_v2(
+        &self,
+        options: SetPolicyOptions,
+        policies: &[BasicPolicyV2],
     ) -> ZxResult {
         let mut inner = self.inner.lock();
         if !inner.is_empty() {
             return Err(ZxError::BAD_STATE);
         }
+        let mut new_policy = inner.policy;
         for policy in policies {
-            if self.parent_policy.get_action(policy.condition).is_some() {
-                match options {
-                    SetPolicyOptions::Absolute => return Err(ZxError::ALREADY_EXISTS),
-                    SetPolicyOptions::Relative => {}
+            for &condition in policy.condition.expand() {
+                if !self.parent_policy.can_override(condition)
+                    && self.parent_policy.get_action(condition) != Some(policy.action)
+                {
+                    match options {
+                        SetPolicyOptions::Absolute => return Err(ZxError::ALREADY_EXISTS),
+                        SetPolicyOptions::Relative => continue,
+                    }
                 }
-            } else {
-                inner.policy.apply(*policy);
+                new_policy.apply(BasicPolicyV2 {
+                    condition,
+                    ..*policy
+                });
             }
         }
+        inner.policy = new_policy;
+        Ok(())
+    }
+
+    /// Sets the timer slack policy to an empty job.
+    ///
+    /// The minimum slack is raised to the effective one of the job if it is smaller.
+    pub fn set_policy_timer_slack(&self, slack: TimerSlack) -> ZxResult {
+        let mut inner = self.inner.lock();
+        if !inner.is_empty() {
+            return Err(ZxError::BAD_STATE);
+        }
+        let current = inner.policy.merge(&self.parent_policy).timer_slack();
+        inner.policy.set_timer_slack(TimerSlack {
+            amount: slack.amount.max(current.amount),
+            mode: slack.mode,
+        });
         Ok(())
     }
 
@@ -153,11 +248,40 @@ impl Job {
         }
     }
 
+    /// Whether the job will be killed when the system runs out of memory.
+    pub fn get_kill_on_oom(&self) -> bool {
+        self.inner.lock().kill_on_oom
+    }
+
+    /// Set whether the job should be killed when the system runs out of memory.
+    pub fn set_kill_on_oom(&self, kill: bool) {
+        self.inner.lock().kill_on_oom = kill;
+    }
+
     /// Get KoIDs of Processes.
     pub fn process_ids(&self) -> Vec<KoID> {
         self.inner.lock().processes.iter().map(|p| p.id()).collect()
     }
 
+    /// Get the total runtime of all processes in the job and its children.
+    pub fn get_task_runtime(&self) -> TaskRuntimeInfo {
+        let (processes, children): (Vec<_>, Vec<_>) = {
+            let inner = self.inner.lock();
+            let children = inner.children.iter().filter_map(|j| j.upgrade()).collect();
+            (inner.processes.clone(), children)
+        };
+        let mut info = TaskRuntimeInfo::default();
+        for runtime in processes
+            .iter()
+            .map(|p| p.get_task_runtime())
+            .chain(children.iter().map(|j| j.get_task_runtime()))
+        {
+            info.cpu_time += runtime.cpu_time;
+            info.queue_time += runtime.queue_time;
+        }
+        info
+    }
+
     /// Get KoIDs of children Jobs.
     pub fn children_ids(&self) -> Vec<KoID> {
         self.inner
@@ -175,7 +299,10 @@ impl Job {
     }
 
     /// The job finally terminates.
+    ///
+    /// `JOB_TERMINATED` is asserted to wake up the waiters.
     fn terminate(&self) {
+        self.base.signal_set(Signal::JOB_TERMINATED);
         if let Some(parent) = self.parent.as_ref() {
             parent.remove_child(&self.inner.lock().self_ref)
         }
@@ -232,7 +359,9 @@ impl Drop for Job {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::signal::Slack;
     use crate::task::TASK_RETCODE_SYSCALL_KILL;
+    use core::time::Duration;
 
     #[test]
     fn create() {
@@ -334,6 +463,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn timer_slack() {
+        let root_job = Job::root();
+        let slack = |ms, mode| TimerSlack {
+            amount: Duration::from_millis(ms),
+            mode,
+        };
+        assert_eq!(root_job.policy().timer_slack(), TimerSlack::default());
+        root_job
+            .set_policy_timer_slack(slack(10, Slack::Late))
+            .unwrap();
+        assert_eq!(root_job.policy().timer_slack(), slack(10, Slack::Late));
+
+        // the slack of a child is never less than its parent's
+        let job = root_job.create_child().unwrap();
+        assert_eq!(job.policy().timer_slack(), slack(10, Slack::Late));
+        job.set_policy_timer_slack(slack(5, Slack::Early)).unwrap();
+        assert_eq!(job.policy().timer_slack(), slack(10, Slack::Early));
+        job.set_policy_timer_slack(slack(20, Slack::Center))
+            .unwrap();
+        assert_eq!(job.policy().timer_slack(), slack(20, Slack::Center));
+
+        let proc = Process::create(&job, "proc").unwrap();
+        assert_eq!(proc.timer_slack(), slack(20, Slack::Center));
+        assert_eq!(
+            job.set_policy_timer_slack(slack(30, Slack::Center)),
+            Err(ZxError::BAD_STATE)
+        );
+    }
+
+    #[test]
+    fn policy_override() {
+        let root_job = Job::root();
+        let policy = |condition, action, override_allowed| BasicPolicyV2 {
+            condition,
+            action,
+            override_allowed,
+        };
+        root_job
+            .set_policy_basic_v2(
+                SetPolicyOptions::Absolute,
+                &[
+                    policy(PolicyCondition::NewAny, PolicyAction::Deny, true),
+                    policy(PolicyCondition::BadHandle, PolicyAction::Kill, false),
+                ],
+            )
+            .unwrap();
+        let root_policy = root_job.policy();
+        assert_eq!(
+            root_policy.get_action(PolicyCondition::NewChannel),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(
+            root_policy.get_action(PolicyCondition::NewProfile),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(root_policy.get_action(PolicyCondition::HandleQuota), None);
+        assert!(root_policy.can_override(PolicyCondition::NewTimer));
+        assert!(!root_policy.can_override(PolicyCondition::BadHandle));
+
+        // overridable policies can be changed by the child
+        let job = root_job.create_child().unwrap();
+        job.set_policy_basic_v2(
+            SetPolicyOptions::Absolute,
+            &[
+                policy(PolicyCondition::NewTimer, PolicyAction::Allow, false),
+                policy(PolicyCondition::BadHandle, PolicyAction::Kill, false),
+            ],
+        )
+        .unwrap();
+        let job_policy = job.policy();
+        assert_eq!(
+            job_policy.get_action(PolicyCondition::NewTimer),
+            Some(PolicyAction::Allow)
+        );
+        assert!(!job_policy.can_override(PolicyCondition::NewTimer));
+        assert_eq!(
+            job_policy.get_action(PolicyCondition::NewPort),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(
+            job.set_policy_basic_v2(
+                SetPolicyOptions::Absolute,
+                &[policy(PolicyCondition::BadHandle, PolicyAction::Deny, true)],
+            ),
+            Err(ZxError::ALREADY_EXISTS)
+        );
+        // a failed call leaves the policy unchanged
+        assert!(job.policy() == job_policy);
+
+        let info = job.get_info(Topic::JobPolicy).unwrap();
+        let expected = job_policy.info();
+        assert_eq!(info.as_bytes(1)[..4], expected.conditions.to_ne_bytes());
+        assert_eq!(
+            info.as_bytes(1)[4..8],
+            expected.override_allowed.to_ne_bytes()
+        );
+        assert_eq!(info.as_bytes(1)[8..16], expected.actions.to_ne_bytes());
+        let timer = 1 << PolicyCondition::NewTimer as u32;
+        assert_eq!(expected.conditions & timer, timer);
+        assert_eq!(expected.override_allowed & timer, 0);
+        assert_eq!(
+            (expected.actions >> (4 * PolicyCondition::BadHandle as u32)) & 0xf,
+            PolicyAction::Kill as u64
+        );
+    }
+
     #[test]
     fn parent_child() {
         let root_job = Job::root();
@@ -385,12 +621,18 @@ mod tests {
         assert!(job.inner.lock().killed);
         assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_SYSCALL_KILL));
         assert_eq!(thread.state(), ThreadState::Dying);
+        assert!(!proc.signal().contains(Signal::PROCESS_TERMINATED));
+        assert!(!root_job.signal().contains(Signal::JOB_TERMINATED));
 
         std::mem::drop(current_thread);
         assert!(root_job.inner.lock().killed);
         assert!(job.inner.lock().killed);
         assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_SYSCALL_KILL));
         assert_eq!(thread.state(), ThreadState::Dead);
+        assert!(thread.signal().contains(Signal::THREAD_TERMINATED));
+        assert!(proc.signal().contains(Signal::PROCESS_TERMINATED));
+        assert!(job.signal().contains(Signal::JOB_TERMINATED));
+        assert!(root_job.signal().contains(Signal::JOB_TERMINATED));
 
         // The job has no children.
         let root_job = Job::root();
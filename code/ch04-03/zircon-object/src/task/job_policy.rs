@@ -1,31 +1,108 @@
+use {
+    crate::object::JobPolicyInfo, crate::signal::Slack, core::convert::TryFrom,
+    core::time::Duration, numeric_enum_macro::numeric_enum,
+};
+
 /// Security and resource policies of a job.
-#[derive(Default, Copy, Clone)]
+///
+/// Basic policies are stored as bitsets indexed by `PolicyCondition`,
+/// and the action of each condition takes 4 bits of `actions`.
+#[derive(Default, Copy, Clone, Eq, PartialEq)]
 pub struct JobPolicy {
-    // TODO: use bitset
-    action: [Option<PolicyAction>; 15],
+    /// Conditions whose action has been set.
+    set: u32,
+    /// Conditions whose action can be overridden by child jobs.
+    override_allowed: u32,
+    /// Actions of conditions.
+    actions: u64,
+    timer_slack: Option<TimerSlack>,
 }
 
 impl JobPolicy {
     /// Get the action of a policy `condition`.
     pub fn get_action(&self, condition: PolicyCondition) -> Option<PolicyAction> {
-        self.action[condition as usize]
+        let i = condition as usize;
+        if self.set & (1 << i) == 0 {
+            return None;
+        }
+        let action = (self.actions >> (i * ACTION_BITS)) as u32 & ACTION_MASK;
+        Some(PolicyAction::try_from(action).unwrap())
+    }
+
+    /// Whether the action of `condition` can be overridden by child jobs.
+    ///
+    /// Conditions that have not been set can always be overridden.
+    pub fn can_override(&self, condition: PolicyCondition) -> bool {
+        let bit = 1 << condition as usize;
+        self.set & bit == 0 || self.override_allowed & bit != 0
     }
 
     /// Apply a basic policy.
-    pub fn apply(&mut self, policy: BasicPolicy) {
-        self.action[policy.condition as usize] = Some(policy.action);
+    pub fn apply(&mut self, policy: BasicPolicyV2) {
+        let i = policy.condition as usize;
+        self.set |= 1 << i;
+        if policy.override_allowed {
+            self.override_allowed |= 1 << i;
+        } else {
+            self.override_allowed &= !(1 << i);
+        }
+        self.actions &= !((ACTION_MASK as u64) << (i * ACTION_BITS));
+        self.actions |= (policy.action as u64) << (i * ACTION_BITS);
+    }
+
+    /// Get the timer slack policy.
+    pub fn timer_slack(&self) -> TimerSlack {
+        self.timer_slack.unwrap_or_default()
+    }
+
+    /// Set the timer slack policy.
+    pub fn set_timer_slack(&mut self, slack: TimerSlack) {
+        self.timer_slack = Some(slack);
     }
 
     /// Merge the policy with `parent`'s.
+    ///
+    /// The parent's action of a condition is taken unless it can be
+    /// overridden and the child has set its own one.
+    ///
+    /// The timer slack of a job is never less than its parent's,
+    /// so the child's one is kept if it has been set.
     pub fn merge(&self, parent: &Self) -> Self {
-        let mut new = *self;
-        for i in 0..15 {
-            if parent.action[i].is_some() {
-                new.action[i] = parent.action[i];
-            }
+        let inherited = parent.set & !(parent.override_allowed & self.set);
+        let mask = action_mask(inherited);
+        JobPolicy {
+            set: self.set | parent.set,
+            override_allowed: (self.override_allowed & !inherited)
+                | (parent.override_allowed & inherited),
+            actions: (self.actions & !mask) | (parent.actions & mask),
+            timer_slack: self.timer_slack.or(parent.timer_slack),
         }
-        new
     }
+
+    /// Get the information of the policy, i.e. the result of `Topic::JobPolicy`.
+    pub fn info(&self) -> JobPolicyInfo {
+        let slack = self.timer_slack();
+        JobPolicyInfo {
+            conditions: self.set,
+            override_allowed: self.override_allowed,
+            actions: self.actions,
+            timer_slack_min: slack.amount.as_nanos() as i64,
+            timer_slack_mode: slack.mode as u32,
+            padding: 0,
+        }
+    }
+}
+
+const ACTION_BITS: usize = 4;
+const ACTION_MASK: u32 = (1 << ACTION_BITS) - 1;
+
+/// Get the mask of actions of the `conditions` bitset.
+fn action_mask(conditions: u32) -> u64 {
+    (0..POLICY_CONDITION_COUNT)
+        .filter(|i| conditions & (1 << i) != 0)
+        .fold(0, |mask, i| {
+            mask | (ACTION_MASK as u64) << (i * ACTION_BITS)
+        })
 }
 
 /// Control the effect in the case of conflict between
@@ -48,61 +125,141 @@ pub struct BasicPolicy {
     pub action: PolicyAction,
 }
 
-/// The condition when a policy is applied.
-#[repr(u32)]
+/// The policy type with an override flag.
 #[derive(Debug, Copy, Clone)]
-pub enum PolicyCondition {
-    /// A process under this job is attempting to issue a syscall with an invalid handle.
-    /// In this case, `PolicyAction::Allow` and `PolicyAction::Deny` are equivalent:
-    /// if the syscall returns, it will always return the error ZX_ERR_BAD_HANDLE.
-    BadHandle = 0,
-    /// A process under this job is attempting to issue a syscall with a handle that does not support such operation.
-    WrongObject = 1,
-    /// A process under this job is attempting to map an address region with write-execute access.
-    VmarWx = 2,
-    /// A special condition that stands for all of the above ZX_NEW conditions
-    /// such as NEW_VMO, NEW_CHANNEL, NEW_EVENT, NEW_EVENTPAIR, NEW_PORT, NEW_SOCKET, NEW_FIFO,
-    /// And any future ZX_NEW policy.
-    /// This will include any new kernel objects which do not require a parent object for creation.
-    NewAny = 3,
-    /// A process under this job is attempting to create a new vm object.
-    NewVMO = 4,
-    /// A process under this job is attempting to create a new channel.
-    NewChannel = 5,
-    /// A process under this job is attempting to create a new event.
-    NewEvent = 6,
-    /// A process under this job is attempting to create a new event pair.
-    NewEventPair = 7,
-    /// A process under this job is attempting to create a new port.
-    NewPort = 8,
-    /// A process under this job is attempting to create a new socket.
-    NewSocket = 9,
-    /// A process under this job is attempting to create a new fifo.
-    NewFIFO = 10,
-    /// A process under this job is attempting to create a new timer.
-    NewTimer = 11,
-    /// A process under this job is attempting to create a new process.
-    NewProcess = 12,
-    /// A process under this job is attempting to create a new profile.
-    NewProfile = 13,
-    /// A process under this job is attempting to use zx_vmo_replace_as_executable()
-    /// with a ZX_HANDLE_INVALID as the second argument rather than a valid ZX_RSRC_KIND_VMEX.
-    AmbientMarkVMOExec = 14,
+pub struct BasicPolicyV2 {
+    /// Condition when the policy is applied.
+    pub condition: PolicyCondition,
+    /// The action taken when the condition happens.
+    pub action: PolicyAction,
+    /// Whether child jobs can override the policy, see `POLICY_OVERRIDE_ALLOW`.
+    pub override_allowed: bool,
 }
 
-/// The action taken when the condition happens specified by a policy.
-#[repr(u32)]
+/// Child jobs can override the policy.
+pub const POLICY_OVERRIDE_ALLOW: u32 = 0;
+/// Child jobs can not override the policy.
+pub const POLICY_OVERRIDE_DENY: u32 = 1;
+
+impl From<BasicPolicy> for BasicPolicyV2 {
+    /// Policies of the first version can not be overridden.
+    fn from(policy: BasicPolicy) -> Self {
+        BasicPolicyV2 {
+            condition: policy.condition,
+            action: policy.action,
+            override_allowed: false,
+        }
+    }
+}
+
+numeric_enum! {
+    #[repr(u32)]
+    /// The condition when a policy is applied.
+    #[derive(Debug, Copy, Clone)]
+    pub enum PolicyCondition {
+        /// A process under this job is attempting to issue a syscall with an invalid handle.
+        /// In this case, `PolicyAction::Allow` and `PolicyAction::Deny` are equivalent:
+        /// if the syscall returns, it will always return the error ZX_ERR_BAD_HANDLE.
+        BadHandle = 0,
+        /// A process under this job is attempting to issue a syscall with a handle that does not support such operation.
+        WrongObject = 1,
+        /// A process under this job is attempting to map an address region with write-execute access.
+        VmarWx = 2,
+        /// A special condition that stands for all of the above ZX_NEW conditions
+        /// such as NEW_VMO, NEW_CHANNEL, NEW_EVENT, NEW_EVENTPAIR, NEW_PORT, NEW_SOCKET, NEW_FIFO,
+        /// And any future ZX_NEW policy.
+        /// This will include any new kernel objects which do not require a parent object for creation.
+        NewAny = 3,
+        /// A process under this job is attempting to create a new vm object.
+        NewVMO = 4,
+        /// A process under this job is attempting to create a new channel.
+        NewChannel = 5,
+        /// A process under this job is attempting to create a new event.
+        NewEvent = 6,
+        /// A process under this job is attempting to create a new event pair.
+        NewEventPair = 7,
+        /// A process under this job is attempting to create a new port.
+        NewPort = 8,
+        /// A process under this job is attempting to create a new socket.
+        NewSocket = 9,
+        /// A process under this job is attempting to create a new fifo.
+        NewFIFO = 10,
+        /// A process under this job is attempting to create a new timer.
+        NewTimer = 11,
+        /// A process under this job is attempting to create a new process.
+        NewProcess = 12,
+        /// A process under this job is attempting to create a new profile.
+        NewProfile = 13,
+        /// A process under this job is attempting to use zx_vmo_replace_as_executable()
+        /// with a ZX_HANDLE_INVALID as the second argument rather than a valid ZX_RSRC_KIND_VMEX.
+        AmbientMarkVMOExec = 14,
+        /// A process under this job is attempting to hold more handles than
+        /// the limit of itself or any of its ancestor jobs. (zCore extension)
+        HandleQuota = 15,
+    }
+}
+
+/// The number of policy conditions.
+pub const POLICY_CONDITION_COUNT: usize = 16;
+
+impl PolicyCondition {
+    /// The conditions a policy of `self` is applied to.
+    ///
+    /// `NewAny` stands for all the `New*` conditions.
+    pub(super) fn expand(&self) -> &[PolicyCondition] {
+        use PolicyCondition::*;
+        const NEW_ANY: [PolicyCondition; 10] = [
+            NewVMO,
+            NewChannel,
+            NewEvent,
+            NewEventPair,
+            NewPort,
+            NewSocket,
+            NewFIFO,
+            NewTimer,
+            NewProcess,
+            NewProfile,
+        ];
+        match self {
+            NewAny => &NEW_ANY,
+            _ => core::slice::from_ref(self),
+        }
+    }
+}
+
+numeric_enum! {
+    #[repr(u32)]
+    /// The action taken when the condition happens specified by a policy.
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub enum PolicyAction {
+        /// Allow condition.
+        Allow = 0,
+        /// Prevent condition.
+        Deny = 1,
+        /// Generate an exception via the debug port. An exception generated this
+        /// way acts as a breakpoint. The thread may be resumed after the exception.
+        AllowException = 2,
+        /// Just like `AllowException`, but after resuming condition is denied.
+        DenyException = 3,
+        /// Terminate the process.
+        Kill = 4,
+    }
+}
+
+/// The timer slack policy of a job.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum PolicyAction {
-    /// Allow condition.
-    Allow = 0,
-    /// Prevent condition.
-    Deny = 1,
-    /// Generate an exception via the debug port. An exception generated this
-    /// way acts as a breakpoint. The thread may be resumed after the exception.
-    AllowException = 2,
-    /// Just like `AllowException`, but after resuming condition is denied.
-    DenyException = 3,
-    /// Terminate the process.
-    Kill = 4,
+pub struct TimerSlack {
+    /// The minimum amount of slack of timers.
+    pub amount: Duration,
+    /// The slack mode of timers created with the default mode `Center`.
+    pub mode: Slack,
+}
+
+impl Default for TimerSlack {
+    fn default() -> Self {
+        TimerSlack {
+            amount: Duration::default(),
+            mode: Slack::Center,
+        }
+    }
 }
@@ -1,11 +1,15 @@
 use super::*;
 
+mod handle_table;
 mod job;
 mod job_policy;
 mod process;
+mod suspend_token;
 mod thread;
 
-pub use {self::job::*, self::job_policy::*, self::process::*, self::thread::*};
+pub use {
+    self::job::*, self::job_policy::*, self::process::*, self::suspend_token::*, self::thread::*,
+};
 
 /// Task (Thread, Process, or Job)
 pub trait Task: Sync + Send {
@@ -22,3 +26,9 @@ pub trait Task: Sync + Send {
 
 /// The return code set when a task is killed via zx_task_kill().
 pub const TASK_RETCODE_SYSCALL_KILL: i64 = -1028;
+
+/// The return code set when a task is killed due to a policy violation.
+pub const TASK_RETCODE_POLICY_KILL: i64 = -1029;
+
+/// The return code set when a task is killed due to an unhandled exception.
+pub const TASK_RETCODE_EXCEPTION_KILL: i64 = -1030;
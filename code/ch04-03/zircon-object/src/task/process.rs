@@ -1,12 +1,8 @@
 use {
-    super::{job::Job, job_policy::*, thread::*, *},
-    crate::{error::*, object::*, vm::*},
+    super::{handle_table::*, job::Job, job_policy::*, thread::*, *},
+    crate::{error::*, object::*, signal::Futex, vm::*},
     alloc::{sync::Arc, vec::Vec},
-    core::{
-        future::Future,
-        pin::Pin,
-        task::{Context, Poll},
-    },
+    core::sync::atomic::AtomicI32,
     hashbrown::HashMap,
     spin::Mutex,
 };
@@ -16,6 +12,7 @@ pub struct Process {
     job: Arc<Job>,
     policy: JobPolicy,
     vmar: Arc<VmAddressRegion>,
+    handle_quota: Arc<HandleQuota>,
     inner: Mutex<ProcessInner>,
 }
 
@@ -28,14 +25,49 @@ impl_kobject!(Process
     fn related_koid(&self) -> KoID {
         self.job.id()
     }
+    fn get_info(&self, topic: Topic) -> ZxResult<ObjectInfo> {
+        match topic {
+            Topic::Process => Ok(ObjectInfo::single(self.get_info())),
+            Topic::ProcessThreads => Ok(ObjectInfo::array(&self.thread_ids())),
+            Topic::TaskRuntime => Ok(ObjectInfo::single(self.get_task_runtime())),
+            Topic::HandleQuota => Ok(ObjectInfo::single(self.handle_quota.info())),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+    fn get_property(&self, property: Property) -> ZxResult<usize> {
+        match property {
+            Property::ProcessDebugAddr => Ok(self.get_debug_addr()),
+            Property::ProcessVdsoBaseAddress => Ok(self.get_vdso_base()),
+            Property::HandleLimit => Ok(self.handle_quota.limit()),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+    fn set_property(&self, property: Property, value: usize) -> ZxResult {
+        match property {
+            Property::ProcessDebugAddr => self.set_debug_addr(value),
+            Property::HandleLimit => {
+                self.set_handle_limit(Some(value).filter(|&limit| limit != usize::MAX));
+                Ok(())
+            }
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
 );
 
 #[derive(Default)]
 struct ProcessInner {
-    max_handle_id: u32,
     status: Status,
-    handles: HashMap<HandleValue, Handle>,
+    handles: HandleTable,
+    futexes: HashMap<usize, Arc<Futex>>,
     threads: Vec<Arc<Thread>>,
+    // the address of the dynamic loader's debug structure
+    debug_addr: usize,
+    // the base address of the mapped vDSO
+    vdso_base: usize,
+    // the job to kill when the process terminates, and whether only for a nonzero retcode
+    critical_to_job: Option<(Arc<Job>, bool)>,
+    // the number of times the process has been suspended, new threads are suspended as many
+    suspend_count: usize,
 }
 
 /// Status of a process.
@@ -58,45 +90,158 @@ impl Default for Status {
 impl Process {
     /// Create a new process in the `job`.
     pub fn create(job: &Arc<Job>, name: &str) -> ZxResult<Arc<Self>> {
-        let proc = Arc::new(Process {
+        let handle_quota = Arc::<HandleQuota>::default();
+        let mut quotas = job.handle_quotas();
+        quotas.insert(0, handle_quota.clone());
+        let mut inner = ProcessInner::default();
+        inner.handles = HandleTable::new(quotas);
+        let proc = register_object(Arc::new(Process {
             base: KObjectBase::with_name(name),
             job: job.clone(),
             policy: job.policy(),
             vmar: VmAddressRegion::new_root(),
-            inner: Mutex::new(ProcessInner::default()),
-        });
+            handle_quota,
+            inner: Mutex::new(inner),
+        }));
         job.add_process(proc.clone())?;
         Ok(proc)
     }
 
     /// Get a handle from the process
-    fn get_handle(&self, handle_value: HandleValue) -> ZxResult<Handle> {
-        self.inner.lock().get_handle(handle_value)
+    pub fn get_handle(&self, handle_value: HandleValue) -> ZxResult<Handle> {
+        let result = self.inner.lock().get_handle(handle_value);
+        self.check_lookup(result)
     }
 
     /// 添加一个新的对象句柄
-    pub fn add_handle(&self, handle: Handle) -> HandleValue {
-        self.inner.lock().add_handle(handle)
+    ///
+    /// 句柄表已满或超出句柄配额时返回 `NO_RESOURCES`
+    pub fn add_handle(&self, handle: Handle) -> ZxResult<HandleValue> {
+        let enforce_quota = self.handle_quota_enforced();
+        let result = self.inner.lock().add_handle(handle.clone(), enforce_quota);
+        self.check_handle_quota(result, || self.inner.lock().add_handle(handle, false))
     }
 
     /// 删除一个对象句柄
+    #[track_caller]
     pub fn remove_handle(&self, handle_value: HandleValue) -> ZxResult<Handle> {
-        self.inner.lock().remove_handle(handle_value)
+        let result = self.inner.lock().remove_handle(handle_value);
+        self.check_lookup(result)
     }
 
-    /// Add all handles to the process
-    pub fn add_handles(&self, handles: Vec<Handle>) -> Vec<HandleValue> {
-        let mut inner = self.inner.lock();
-        handles.into_iter().map(|h| inner.add_handle(h)).collect()
+    /// Add all handles to the process.
+    ///
+    /// Either all handles are added, or none of them if there is no room for all.
+    pub fn add_handles(&self, handles: Vec<Handle>) -> ZxResult<Vec<HandleValue>> {
+        let enforce_quota = self.handle_quota_enforced();
+        let result = self
+            .inner
+            .lock()
+            .add_handles(handles.clone(), enforce_quota);
+        self.check_handle_quota(result, || self.inner.lock().add_handles(handles, false))
     }
 
     /// Remove all handles from the process.
+    #[track_caller]
     pub fn remove_handles(&self, handle_values: &[HandleValue]) -> ZxResult<Vec<Handle>> {
         let mut inner = self.inner.lock();
-        handle_values
-            .iter()
-            .map(|h| inner.remove_handle(*h))
-            .collect()
+        let mut handles = Vec::with_capacity(handle_values.len());
+        for &handle_value in handle_values {
+            match inner.remove_handle(handle_value) {
+                Ok(handle) => handles.push(handle),
+                Err(err) => {
+                    drop(inner);
+                    return self.check_lookup(Err(err));
+                }
+            }
+        }
+        Ok(handles)
+    }
+
+    /// Duplicate a handle with the rights decided by `op` on the rights of the original one.
+    pub fn dup_handle_operating_rights(
+        &self,
+        handle_value: HandleValue,
+        op: impl FnOnce(Rights) -> ZxResult<Rights>,
+    ) -> ZxResult<HandleValue> {
+        let mut handle = self.get_handle(handle_value)?;
+        handle.rights = op(handle.rights)?;
+        self.add_handle(handle)
+    }
+
+    /// Replace a handle with a new one with the rights decided by `op`.
+    ///
+    /// The original handle is always removed, even if `op` fails.
+    #[track_caller]
+    pub fn replace_handle_operating_rights(
+        &self,
+        handle_value: HandleValue,
+        op: impl FnOnce(Rights) -> ZxResult<Rights>,
+    ) -> ZxResult<HandleValue> {
+        let mut handle = self.remove_handle(handle_value)?;
+        handle.rights = op(handle.rights)?;
+        self.add_handle(handle)
+    }
+
+    /// Set the maximum number of handles held by the process, `None` for unlimited.
+    pub fn set_handle_limit(&self, limit: Option<usize>) {
+        self.handle_quota.set_limit(limit);
+    }
+
+    /// Check that `count` handles can be added to the process.
+    ///
+    /// Return `NO_RESOURCES` if the handle table is full, or if a handle quota
+    /// would be exceeded and the `HandleQuota` policy does not allow it.
+    /// No policy action is taken, as no handle is added.
+    pub fn check_handle_room(&self, count: usize) -> ZxResult {
+        let enforce_quota = !matches!(
+            self.policy.get_action(PolicyCondition::HandleQuota),
+            Some(PolicyAction::Allow) | Some(PolicyAction::AllowException)
+        );
+        if !self.inner.lock().handles.has_room(count, enforce_quota) {
+            return Err(ZxError::NO_RESOURCES);
+        }
+        Ok(())
+    }
+
+    /// Whether the handle quotas are enforced.
+    ///
+    /// They are enforced unless the `HandleQuota` policy is `Allow`.
+    fn handle_quota_enforced(&self) -> bool {
+        self.policy.get_action(PolicyCondition::HandleQuota) != Some(PolicyAction::Allow)
+    }
+
+    /// Apply the `HandleQuota` policy if a handle quota is exceeded.
+    ///
+    /// If the policy is `AllowException`, the exception is raised and the handles
+    /// are added again by `add` without enforcing the quotas. Otherwise the error
+    /// is reported as `NO_RESOURCES`.
+    fn check_handle_quota<T>(
+        &self,
+        result: ZxResult<T>,
+        add: impl FnOnce() -> ZxResult<T>,
+    ) -> ZxResult<T> {
+        if let Err(ZxError::INTERNAL_QUOTA_EXCEEDED) = result {
+            let action = self.policy.get_action(PolicyCondition::HandleQuota);
+            let allowed = self.check_policy(PolicyCondition::HandleQuota).is_ok();
+            if allowed && action == Some(PolicyAction::AllowException) {
+                return add();
+            }
+            return Err(ZxError::NO_RESOURCES);
+        }
+        result
+    }
+
+    /// Apply the `BadHandle` or `WrongObject` policy if a handle lookup failed.
+    fn check_lookup<T>(&self, result: ZxResult<T>) -> ZxResult<T> {
+        let condition = match result {
+            Err(ZxError::BAD_HANDLE) => PolicyCondition::BadHandle,
+            Err(ZxError::WRONG_TYPE) => PolicyCondition::WrongObject,
+            _ => return result,
+        };
+        // the original error is returned whatever the action is
+        self.check_policy(condition).ok();
+        result
     }
 
     /// Get the kernel object corresponding to this `handle_value`
@@ -105,7 +250,8 @@ impl Process {
         let object = handle
             .object
             .downcast_arc::<T>()
-            .map_err(|_| ZxError::WRONG_TYPE)?;
+            .map_err(|_| ZxError::WRONG_TYPE);
+        let object = self.check_lookup(object)?;
         Ok(object)
     }
 
@@ -120,13 +266,28 @@ impl Process {
         let object = handle
             .object
             .downcast_arc::<T>()
-            .map_err(|_| ZxError::WRONG_TYPE)?;
+            .map_err(|_| ZxError::WRONG_TYPE);
+        let object = self.check_lookup(object)?;
         if !handle.rights.contains(desired_rights) {
             return Err(ZxError::ACCESS_DENIED);
         }
         Ok(object)
     }
 
+    /// Get the kernel object corresponding to this `handle_value`,
+    /// after checking that this handle has the `desired_rights`.
+    pub fn get_dyn_object_with_rights(
+        &self,
+        handle_value: HandleValue,
+        desired_rights: Rights,
+    ) -> ZxResult<Arc<dyn KernelObject>> {
+        let handle = self.get_handle(handle_value)?;
+        if !handle.rights.contains(desired_rights) {
+            return Err(ZxError::ACCESS_DENIED);
+        }
+        Ok(handle.object)
+    }
+
     /// Get the kernel object corresponding to this `handle_value` and this handle's rights.
     pub fn get_object_and_rights<T: KernelObject>(
         &self,
@@ -136,17 +297,20 @@ impl Process {
         let object = handle
             .object
             .downcast_arc::<T>()
-            .map_err(|_| ZxError::WRONG_TYPE)?;
+            .map_err(|_| ZxError::WRONG_TYPE);
+        let object = self.check_lookup(object)?;
         Ok((object, handle.rights))
     }
 
     /// Remove a handle referring to a kernel object of the given type from the process.
+    #[track_caller]
     pub fn remove_object<T: KernelObject>(&self, handle_value: HandleValue) -> ZxResult<Arc<T>> {
         let handle = self.remove_handle(handle_value)?;
         let object = handle
             .object
             .downcast_arc::<T>()
-            .map_err(|_| ZxError::WRONG_TYPE)?;
+            .map_err(|_| ZxError::WRONG_TYPE);
+        let object = self.check_lookup(object)?;
         Ok(object)
     }
 
@@ -159,17 +323,30 @@ impl Process {
         arg2: usize,
         thread_fn: ThreadFn,
     ) -> ZxResult {
-        let handle_value;
-        {
-            let mut inner = self.inner.lock();
+        let check = |inner: &ProcessInner| {
             if !inner.contains_thread(thread) {
                 return Err(ZxError::ACCESS_DENIED);
             }
             if inner.status != Status::Init {
                 return Err(ZxError::BAD_STATE);
             }
+            Ok(())
+        };
+        check(&self.inner.lock())?;
+        // the handle quota policy may kill the process, so add the handle without the lock
+        let handle_value = match arg1 {
+            Some(handle) => self.add_handle(handle)?,
+            None => INVALID_HANDLE,
+        };
+        {
+            let mut inner = self.inner.lock();
+            if let Err(err) = check(&inner) {
+                if handle_value != INVALID_HANDLE {
+                    inner.remove_handle(handle_value).ok();
+                }
+                return Err(err);
+            }
             inner.status = Status::Running;
-            handle_value = arg1.map_or(INVALID_HANDLE, |handle| inner.add_handle(handle));
         }
         thread.set_first_thread();
         match thread.start(entry, stack, handle_value as usize, arg2, thread_fn) {
@@ -194,7 +371,8 @@ impl Process {
         }
         inner.status = Status::Exited(retcode);
         if inner.threads.is_empty() {
-            inner.handles.clear();
+            inner.clear_handles();
+            inner.futexes.clear();
             drop(inner);
             self.terminate();
             return;
@@ -202,23 +380,70 @@ impl Process {
         for thread in inner.threads.iter() {
             thread.kill();
         }
-        inner.handles.clear();
+        inner.clear_handles();
+        inner.futexes.clear();
     }
 
     /// The process finally terminates.
+    ///
+    /// `PROCESS_TERMINATED` is asserted to wake up the waiters.
     fn terminate(&self) {
         let mut inner = self.inner.lock();
-        let _retcode = match inner.status {
+        let retcode = match inner.status {
             Status::Exited(retcode) => retcode,
             _ => {
                 inner.status = Status::Exited(0);
                 0
             }
         };
+        let critical_to_job = inner.critical_to_job.take();
+        drop(inner);
+        self.base.signal_set(Signal::PROCESS_TERMINATED);
         self.job.remove_process(self.base.id);
+        if let Some((job, retcode_nonzero)) = critical_to_job {
+            if !retcode_nonzero || retcode != 0 {
+                job.kill();
+            }
+        }
+    }
+
+    /// Make the process critical to `job`, which is its job or an ancestor.
+    ///
+    /// The `job` is killed when the process terminates,
+    /// or only when it exits with a nonzero retcode if `retcode_nonzero`.
+    pub fn set_critical_job(&self, job: &Arc<Job>, retcode_nonzero: bool) -> ZxResult {
+        let mut ancestor = Some(self.job.clone());
+        while let Some(j) = ancestor {
+            if Arc::ptr_eq(&j, job) {
+                let mut inner = self.inner.lock();
+                if inner.critical_to_job.is_some() {
+                    return Err(ZxError::ALREADY_BOUND);
+                }
+                inner.critical_to_job = Some((job.clone(), retcode_nonzero));
+                return Ok(());
+            }
+            ancestor = j.parent();
+        }
+        Err(ZxError::INVALID_ARGS)
+    }
+
+    /// Get the futex on the user value at `value`, create one if not exist.
+    ///
+    /// Futexes are keyed by the virtual address of their values.
+    pub fn get_futex(&self, value: &'static AtomicI32) -> Arc<Futex> {
+        let mut inner = self.inner.lock();
+        inner
+            .futexes
+            .entry(value as *const AtomicI32 as usize)
+            .or_insert_with(|| Futex::new(value))
+            .clone()
     }
 
     /// Check whether `condition` is allowed in the parent job's policy.
+    ///
+    /// Return `ACCESS_DENIED` if the action is `Deny`, `DenyException` or `Kill`.
+    /// The process is killed for `Kill`, and a policy exception is raised
+    /// for `AllowException` and `DenyException`.
     pub fn check_policy(&self, condition: PolicyCondition) -> ZxResult {
         match self
             .policy
@@ -227,15 +452,71 @@ impl Process {
         {
             PolicyAction::Allow => Ok(()),
             PolicyAction::Deny => Err(ZxError::ACCESS_DENIED),
-            _ => unimplemented!(),
+            PolicyAction::AllowException => {
+                self.raise_policy_exception(condition);
+                Ok(())
+            }
+            PolicyAction::DenyException => {
+                self.raise_policy_exception(condition);
+                Err(ZxError::ACCESS_DENIED)
+            }
+            PolicyAction::Kill => {
+                warn!("process {} killed by policy {:?}", self.base.id, condition);
+                self.exit(TASK_RETCODE_POLICY_KILL);
+                Err(ZxError::ACCESS_DENIED)
+            }
         }
     }
 
+    /// Raise a policy exception on `condition`.
+    ///
+    /// No exception handler can be installed yet, so the exception is never handled,
+    /// and the process is killed as Zircon does with an unhandled exception.
+    fn raise_policy_exception(&self, condition: PolicyCondition) {
+        warn!(
+            "unhandled policy exception {:?} in process {}",
+            condition, self.base.id
+        );
+        self.exit(TASK_RETCODE_EXCEPTION_KILL);
+    }
+
+    /// Get the timer slack policy of the process.
+    pub fn timer_slack(&self) -> TimerSlack {
+        self.policy.timer_slack()
+    }
+
     /// Get process status.
     pub fn status(&self) -> Status {
         self.inner.lock().status
     }
 
+    /// Get the address of the dynamic loader's debug structure.
+    pub fn get_debug_addr(&self) -> usize {
+        self.inner.lock().debug_addr
+    }
+
+    /// Set the address of the dynamic loader's debug structure.
+    ///
+    /// The address can only be changed while the process is alive.
+    pub fn set_debug_addr(&self, addr: usize) -> ZxResult {
+        let mut inner = self.inner.lock();
+        if let Status::Exited(_) = inner.status {
+            return Err(ZxError::BAD_STATE);
+        }
+        inner.debug_addr = addr;
+        Ok(())
+    }
+
+    /// Get the base address of the vDSO mapped in the process.
+    pub fn get_vdso_base(&self) -> usize {
+        self.inner.lock().vdso_base
+    }
+
+    /// Record the base address of the vDSO mapped in the process.
+    pub fn set_vdso_base(&self, addr: usize) {
+        self.inner.lock().vdso_base = addr;
+    }
+
     /// Get the `VmAddressRegion` of the process.
     pub fn vmar(&self) -> Arc<VmAddressRegion> {
         self.vmar.clone()
@@ -252,6 +533,9 @@ impl Process {
         if let Status::Exited(_) = inner.status {
             return Err(ZxError::BAD_STATE);
         }
+        for _ in 0..inner.suspend_count {
+            thread.suspend();
+        }
         inner.threads.push(thread);
         Ok(())
     }
@@ -273,6 +557,15 @@ impl Process {
         self.inner.lock().threads.iter().map(|t| t.id()).collect()
     }
 
+    /// Get the total runtime of all threads in the process.
+    pub fn get_task_runtime(&self) -> TaskRuntimeInfo {
+        let inner = self.inner.lock();
+        TaskRuntimeInfo {
+            cpu_time: inner.threads.iter().map(|t| t.get_time() as i64).sum(),
+            queue_time: 0,
+        }
+    }
+
     /// Get information of this process.
     pub fn get_info(&self) -> ProcessInfo {
         let mut info = ProcessInfo {
@@ -298,25 +591,13 @@ impl Process {
 }
 
 impl Process {
-    pub fn wait_for_end(self: Arc<Self>) -> impl Future<Output = i64> {
-        struct ProcessEndFuture {
-            proc: Arc<Process>,
-        }
-        impl Future for ProcessEndFuture {
-            type Output = i64;
-
-            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
-                if let Status::Exited(exit_code) = self.proc.status() {
-                    Poll::Ready(exit_code)
-                } else {
-                    let waker = cx.waker().clone();
-                    waker.wake_by_ref();
-                    Poll::Pending
-                }
-            }
-        }
-        ProcessEndFuture {
-            proc: Arc::clone(&self),
+    /// Wait for the process to terminate, then return its exit code.
+    pub async fn wait_for_end(self: Arc<Self>) -> i64 {
+        let object: Arc<dyn KernelObject> = self.clone();
+        object.wait_signal(Signal::PROCESS_TERMINATED).await;
+        match self.status() {
+            Status::Exited(exit_code) => exit_code,
+            _ => unreachable!(),
         }
     }
 }
@@ -329,6 +610,8 @@ pub struct ProcessInfo {
     pub return_code: i64,
     pub started: bool,
     pub has_exited: bool,
+    pub debugger_attached: bool,
+    padding1: [u8; 5],
 }
 
 impl Task for Process {
@@ -337,14 +620,17 @@ impl Task for Process {
     }
 
     fn suspend(&self) {
-        let inner = self.inner.lock();
+        let mut inner = self.inner.lock();
+        inner.suspend_count += 1;
         for thread in inner.threads.iter() {
             thread.suspend();
         }
     }
 
     fn resume(&self) {
-        let inner = self.inner.lock();
+        let mut inner = self.inner.lock();
+        assert_ne!(inner.suspend_count, 0);
+        inner.suspend_count -= 1;
         for thread in inner.threads.iter() {
             thread.resume();
         }
@@ -353,24 +639,43 @@ impl Task for Process {
 
 impl ProcessInner {
     /// Add a handle to the process
-    fn add_handle(&mut self, handle: Handle) -> HandleValue {
-        let key = (self.max_handle_id << 2) | 0x3u32;
-        self.max_handle_id += 1;
-        self.handles.insert(key, handle);
-        key
+    fn add_handle(&mut self, handle: Handle, enforce_quota: bool) -> ZxResult<HandleValue> {
+        let object = handle.object.clone();
+        let handle_value = self.handles.insert(handle, enforce_quota)?;
+        object.inc_handle_count();
+        Ok(handle_value)
+    }
+
+    /// Add all handles to the process, or none of them on failure.
+    fn add_handles(
+        &mut self,
+        handles: Vec<Handle>,
+        enforce_quota: bool,
+    ) -> ZxResult<Vec<HandleValue>> {
+        let objects: Vec<_> = handles.iter().map(|h| h.object.clone()).collect();
+        let handle_values = self.handles.insert_all(handles, enforce_quota)?;
+        for object in objects.iter() {
+            object.inc_handle_count();
+        }
+        Ok(handle_values)
     }
 
+    #[track_caller]
     fn remove_handle(&mut self, handle_value: HandleValue) -> ZxResult<Handle> {
-        let handle = self
-            .handles
-            .remove(&handle_value)
-            .ok_or(ZxError::BAD_HANDLE)?;
+        let handle = self.handles.remove(handle_value)?;
+        handle.object.dec_handle_count();
         Ok(handle)
     }
 
+    /// Remove all handles from the table.
+    fn clear_handles(&mut self) {
+        for handle in self.handles.clear() {
+            handle.object.dec_handle_count();
+        }
+    }
+
     fn get_handle(&mut self, handle_value: HandleValue) -> ZxResult<Handle> {
-        let handle = self.handles.get(&handle_value).ok_or(ZxError::BAD_HANDLE)?;
-        Ok(handle.clone())
+        self.handles.get(handle_value).cloned()
     }
 
     /// Whether `thread` is in this process.
@@ -379,6 +684,12 @@ impl ProcessInner {
     }
 }
 
+impl Drop for ProcessInner {
+    fn drop(&mut self) {
+        self.clear_handles();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,7 +709,7 @@ mod tests {
         let proc = Process::create(&root_job, "proc").expect("failed to create process");
         let handle = Handle::new(proc.clone(), Rights::DEFAULT_PROCESS);
 
-        let handle_value = proc.add_handle(handle);
+        let handle_value = proc.add_handle(handle).unwrap();
 
         // getting object should success
         let object: Arc<Process> = proc
@@ -438,7 +749,7 @@ mod tests {
         let handle1 = Handle::new(proc.clone(), Rights::DEFAULT_PROCESS);
         let handle2 = Handle::new(proc.clone(), Rights::DEFAULT_PROCESS);
 
-        let handle_values = proc.add_handles(vec![handle1, handle2]);
+        let handle_values = proc.add_handles(vec![handle1, handle2]).unwrap();
         let object1: Arc<Process> = proc
             .get_object_with_rights(handle_values[0], Rights::DEFAULT_PROCESS)
             .expect("failed to get object");
@@ -452,6 +763,194 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handle_quota() {
+        let root_job = Job::root();
+        let job = root_job.create_child().unwrap();
+        job.set_handle_limit(Some(3));
+        let proc = Process::create(&job, "proc").expect("failed to create process");
+        proc.set_handle_limit(Some(2));
+        let other = Process::create(&job, "other").expect("failed to create process");
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+
+        proc.add_handles(vec![handle.clone(), handle.clone()])
+            .unwrap();
+        assert_eq!(
+            proc.add_handle(handle.clone()).err(),
+            Some(ZxError::NO_RESOURCES)
+        );
+        other.add_handle(handle.clone()).unwrap();
+        assert_eq!(
+            other.add_handle(handle.clone()).err(),
+            Some(ZxError::NO_RESOURCES)
+        );
+
+        let object: Arc<dyn KernelObject> = job.clone();
+        let info = object.get_info(Topic::HandleQuota).unwrap();
+        let mut expected = 3u64.to_ne_bytes().to_vec();
+        expected.extend_from_slice(&3u64.to_ne_bytes());
+        assert_eq!(info.as_bytes(1), expected.as_slice());
+
+        // the limit can also be changed as a property
+        assert_eq!(object.get_property(Property::HandleLimit), Ok(3));
+        object
+            .set_property(Property::HandleLimit, usize::MAX)
+            .unwrap();
+        assert_eq!(object.get_property(Property::HandleLimit), Ok(usize::MAX));
+        other.add_handle(handle.clone()).unwrap();
+        object.set_property(Property::HandleLimit, 3).unwrap();
+
+        // handles of an exited process are no longer charged
+        proc.exit(0);
+        other.add_handle(handle).unwrap();
+    }
+
+    #[test]
+    fn handle_quota_kill() {
+        let root_job = Job::root();
+        let job = root_job.create_child().unwrap();
+        job.set_policy_basic(
+            SetPolicyOptions::Absolute,
+            &[BasicPolicy {
+                condition: PolicyCondition::HandleQuota,
+                action: PolicyAction::Kill,
+            }],
+        )
+        .unwrap();
+        let proc = Process::create(&job, "proc").expect("failed to create process");
+        proc.set_handle_limit(Some(0));
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        assert_eq!(proc.add_handle(handle).err(), Some(ZxError::NO_RESOURCES));
+        assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_POLICY_KILL));
+    }
+
+    #[test]
+    fn handle_quota_read() {
+        use crate::ipc::{Channel, MessagePacket};
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        proc.set_handle_limit(Some(1));
+        let (channel0, channel1) = Channel::create();
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        proc.add_handle(handle.clone()).unwrap();
+        channel1
+            .write(MessagePacket {
+                data: vec![0],
+                handles: vec![handle],
+            })
+            .unwrap();
+
+        // the message is kept if its handles exceed the quota
+        let read = || channel0.check_and_read(|msg| proc.check_handle_room(msg.handles.len()));
+        assert_eq!(read().err(), Some(ZxError::NO_RESOURCES));
+        assert_eq!(proc.status(), Status::Init);
+        proc.set_handle_limit(Some(2));
+        let msg = read().unwrap();
+        proc.add_handles(msg.handles).unwrap();
+    }
+
+    #[test]
+    fn handle_quota_allow() {
+        let root_job = Job::root();
+        let job = root_job.create_child().unwrap();
+        job.set_policy_basic(
+            SetPolicyOptions::Absolute,
+            &[BasicPolicy {
+                condition: PolicyCondition::HandleQuota,
+                action: PolicyAction::Allow,
+            }],
+        )
+        .unwrap();
+        let proc = Process::create(&job, "proc").expect("failed to create process");
+        proc.set_handle_limit(Some(0));
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        proc.add_handle(handle).unwrap();
+        assert_eq!(proc.status(), Status::Init);
+    }
+
+    #[test]
+    fn handle_count() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let object = DummyObject::new();
+        assert_eq!(object.handle_count(), 0);
+
+        let handle_value = proc
+            .add_handle(Handle::new(object.clone(), Rights::DEFAULT_EVENT))
+            .unwrap();
+        proc.dup_handle_operating_rights(handle_value, Ok).unwrap();
+        assert_eq!(object.handle_count(), 2);
+
+        proc.remove_handle(handle_value).unwrap();
+        assert_eq!(object.handle_count(), 1);
+
+        proc.exit(0);
+        assert_eq!(object.handle_count(), 0);
+    }
+
+    #[test]
+    fn get_info() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let thread = Thread::create(&proc, "thread").expect("failed to create thread");
+        let object: Arc<dyn KernelObject> = proc.clone();
+
+        let info = object.get_info(Topic::ProcessThreads).unwrap();
+        assert!(info.is_array());
+        assert_eq!(info.as_bytes(1), &thread.id().to_ne_bytes());
+
+        let info = object.get_info(Topic::Process).unwrap();
+        assert_eq!(info.record_size(), core::mem::size_of::<ProcessInfo>());
+        assert_eq!(
+            object.get_info(Topic::JobChildren).err(),
+            Some(ZxError::WRONG_TYPE)
+        );
+
+        let info = root_job.get_info(Topic::JobProcesses).unwrap();
+        assert_eq!(info.count(), 1);
+        assert_eq!(info.as_bytes(1), &proc.id().to_ne_bytes());
+
+        thread.time_add(10);
+        let info = root_job.get_info(Topic::TaskRuntime).unwrap();
+        assert_eq!(&info.as_bytes(1)[..8], &10i64.to_ne_bytes());
+    }
+
+    #[test]
+    fn duplicate_replace() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let handle_value = proc
+            .add_handle(Handle::new(proc.clone(), Rights::DEFAULT_PROCESS))
+            .unwrap();
+
+        let dup_value = proc
+            .dup_handle_operating_rights(handle_value, |_| Ok(Rights::BASIC))
+            .unwrap();
+        assert_ne!(dup_value, handle_value);
+        let (_, rights) = proc.get_object_and_rights::<Process>(dup_value).unwrap();
+        assert_eq!(rights, Rights::BASIC);
+
+        // a failed replacement still removes the original handle
+        assert_eq!(
+            proc.replace_handle_operating_rights(dup_value, |_| Err(ZxError::INVALID_ARGS)),
+            Err(ZxError::INVALID_ARGS)
+        );
+        assert_eq!(
+            proc.remove_handle(dup_value).err(),
+            Some(ZxError::BAD_HANDLE)
+        );
+
+        let new_value = proc
+            .replace_handle_operating_rights(handle_value, Ok)
+            .unwrap();
+        assert_eq!(
+            proc.remove_handle(handle_value).err(),
+            Some(ZxError::BAD_HANDLE)
+        );
+        let (_, rights) = proc.get_object_and_rights::<Process>(new_value).unwrap();
+        assert_eq!(rights, Rights::DEFAULT_PROCESS);
+    }
+
     #[test]
     fn get_child() {
         let root_job = Job::root();
@@ -531,4 +1030,161 @@ mod tests {
             Some(ZxError::BAD_STATE)
         );
     }
+
+    #[test]
+    fn policy_action() {
+        let policy = |condition, action| BasicPolicy { condition, action };
+        let create = |policies: &[BasicPolicy]| {
+            let job = Job::root();
+            job.set_policy_basic(SetPolicyOptions::Absolute, policies)
+                .unwrap();
+            Process::create(&job, "proc").unwrap()
+        };
+
+        // the error of a lookup is kept, and the process is killed
+        let proc = create(&[policy(PolicyCondition::BadHandle, PolicyAction::Kill)]);
+        assert_eq!(
+            proc.get_handle(INVALID_HANDLE).err(),
+            Some(ZxError::BAD_HANDLE)
+        );
+        assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_POLICY_KILL));
+
+        let proc = create(&[policy(
+            PolicyCondition::WrongObject,
+            PolicyAction::DenyException,
+        )]);
+        let handle_value = proc
+            .add_handle(Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT))
+            .unwrap();
+        assert!(proc.get_object::<DummyObject>(handle_value).is_ok());
+        assert_eq!(proc.status(), Status::Init);
+        assert_eq!(
+            proc.get_object::<Process>(handle_value).err(),
+            Some(ZxError::WRONG_TYPE)
+        );
+        assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_EXCEPTION_KILL));
+
+        let proc = create(&[
+            policy(PolicyCondition::NewChannel, PolicyAction::AllowException),
+            policy(PolicyCondition::NewEvent, PolicyAction::DenyException),
+        ]);
+        assert_eq!(proc.check_policy(PolicyCondition::NewChannel), Ok(()));
+        assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_EXCEPTION_KILL));
+        assert_eq!(
+            proc.check_policy(PolicyCondition::NewEvent),
+            Err(ZxError::ACCESS_DENIED)
+        );
+
+        // exceeding the handle quota raises an exception, and is allowed or not
+        let handle = Handle::new(DummyObject::new(), Rights::DEFAULT_EVENT);
+        let proc = create(&[policy(
+            PolicyCondition::HandleQuota,
+            PolicyAction::AllowException,
+        )]);
+        proc.set_handle_limit(Some(0));
+        assert!(proc.add_handle(handle.clone()).is_ok());
+        assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_EXCEPTION_KILL));
+
+        let proc = create(&[policy(
+            PolicyCondition::HandleQuota,
+            PolicyAction::DenyException,
+        )]);
+        proc.set_handle_limit(Some(0));
+        assert_eq!(
+            proc.add_handles(vec![handle]).err(),
+            Some(ZxError::NO_RESOURCES)
+        );
+        assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_EXCEPTION_KILL));
+    }
+
+    #[async_std::test]
+    async fn policy_kill_blocked() {
+        kernel_hal_unix::init();
+        let job = Job::root();
+        let policy = BasicPolicy {
+            condition: PolicyCondition::BadHandle,
+            action: PolicyAction::Kill,
+        };
+        job.set_policy_basic(SetPolicyOptions::Absolute, &[policy])
+            .unwrap();
+        let proc = Process::create(&job, "proc").unwrap();
+        let thread = start_blocked_thread(&proc).await;
+
+        // the sibling thread blocked in an infinite wait is killed too
+        assert_eq!(
+            proc.get_handle(INVALID_HANDLE).err(),
+            Some(ZxError::BAD_HANDLE)
+        );
+        let object: Arc<dyn KernelObject> = proc.clone();
+        object.wait_signal(Signal::PROCESS_TERMINATED).await;
+        assert_eq!(thread.state(), ThreadState::Dead);
+        assert_eq!(proc.status(), Status::Exited(TASK_RETCODE_POLICY_KILL));
+    }
+
+    #[test]
+    fn critical() {
+        let root_job = Job::root();
+        let job = root_job.create_child().unwrap();
+        let other_job = root_job.create_child().unwrap();
+        let proc = Process::create(&job, "proc").unwrap();
+        assert_eq!(
+            proc.set_critical_job(&other_job, false),
+            Err(ZxError::INVALID_ARGS)
+        );
+        proc.set_critical_job(&root_job, true).unwrap();
+        assert_eq!(
+            proc.set_critical_job(&job, false),
+            Err(ZxError::ALREADY_BOUND)
+        );
+        // a zero retcode does not kill the job
+        proc.exit(0);
+        assert!(!root_job.signal().contains(Signal::JOB_TERMINATED));
+
+        let proc = Process::create(&job, "proc").unwrap();
+        proc.set_critical_job(&root_job, true).unwrap();
+        proc.exit(1);
+        assert!(root_job.signal().contains(Signal::JOB_TERMINATED));
+        assert!(job.signal().contains(Signal::JOB_TERMINATED));
+    }
+
+    #[async_std::test]
+    async fn critical_kill_blocked() {
+        kernel_hal_unix::init();
+        let root_job = Job::root();
+        let job = root_job.create_child().unwrap();
+        let proc = Process::create(&job, "proc").unwrap();
+        let other_proc = Process::create(&job, "other").unwrap();
+        let thread = start_blocked_thread(&other_proc).await;
+        proc.set_critical_job(&job, false).unwrap();
+
+        // the other process of the job is killed with its blocked thread
+        proc.exit(0);
+        let object: Arc<dyn KernelObject> = job.clone();
+        object.wait_signal(Signal::JOB_TERMINATED).await;
+        assert_eq!(thread.state(), ThreadState::Dead);
+        assert_eq!(
+            other_proc.status(),
+            Status::Exited(TASK_RETCODE_SYSCALL_KILL)
+        );
+    }
+
+    #[async_std::test]
+    async fn wait_for_end() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let thread = Thread::create(&proc, "thread").expect("failed to create thread");
+        let current_thread = CurrentThread(thread);
+
+        async_std::task::spawn({
+            let proc = proc.clone();
+            async move {
+                async_std::task::sleep(core::time::Duration::from_millis(10)).await;
+                proc.exit(666);
+                // the process terminates after all threads exit
+                drop(current_thread);
+            }
+        });
+        assert_eq!(proc.clone().wait_for_end().await, 666);
+        assert!(proc.signal().contains(Signal::PROCESS_TERMINATED));
+    }
 }
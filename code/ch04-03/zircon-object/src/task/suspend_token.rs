@@ -0,0 +1,81 @@
+use {super::*, crate::object::*, alloc::sync::Arc};
+
+/// A token of a suspended task.
+///
+/// The task is suspended as long as any of its tokens exists,
+/// and the token resumes the task when dropped.
+pub struct SuspendToken {
+    base: KObjectBase,
+    task: Arc<dyn Task>,
+}
+
+impl_kobject!(SuspendToken);
+
+impl SuspendToken {
+    /// Suspend a thread or a process and create a token of it.
+    ///
+    /// Return `WRONG_TYPE` if `object` is neither a thread nor a process,
+    /// or `BAD_STATE` if the task has terminated or is terminating.
+    pub fn create(object: Arc<dyn KernelObject>) -> ZxResult<Arc<Self>> {
+        let task: Arc<dyn Task> = if let Ok(thread) = object.clone().downcast_arc::<Thread>() {
+            if let ThreadState::Dying | ThreadState::Dead = thread.state() {
+                return Err(ZxError::BAD_STATE);
+            }
+            thread
+        } else if let Ok(process) = object.downcast_arc::<Process>() {
+            if let Status::Exited(_) = process.status() {
+                return Err(ZxError::BAD_STATE);
+            }
+            process
+        } else {
+            return Err(ZxError::WRONG_TYPE);
+        };
+        task.suspend();
+        Ok(register_object(Arc::new(SuspendToken {
+            base: KObjectBase::new(),
+            task,
+        })))
+    }
+}
+
+impl Drop for SuspendToken {
+    fn drop(&mut self) {
+        self.task.resume();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suspend() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let thread = Thread::create(&proc, "thread").expect("failed to create thread");
+        assert_eq!(
+            SuspendToken::create(root_job).err(),
+            Some(ZxError::WRONG_TYPE)
+        );
+
+        let token = SuspendToken::create(thread.clone()).unwrap();
+        assert_eq!(token.obj_type(), ObjectType::SuspendToken);
+        assert_eq!(thread.state(), ThreadState::Suspended);
+        assert!(thread.signal().contains(Signal::THREAD_SUSPENDED));
+
+        // threads created later are suspended with the process
+        let proc_token = SuspendToken::create(proc.clone()).unwrap();
+        let thread1 = Thread::create(&proc, "thread1").expect("failed to create thread");
+        assert_eq!(thread1.state(), ThreadState::Suspended);
+
+        drop(token);
+        assert_eq!(thread.state(), ThreadState::Suspended);
+        drop(proc_token);
+        assert_eq!(thread.state(), ThreadState::New);
+        assert_eq!(thread1.state(), ThreadState::New);
+        assert!(!thread.signal().contains(Signal::THREAD_SUSPENDED));
+
+        proc.kill();
+        assert_eq!(SuspendToken::create(proc).err(), Some(ZxError::BAD_STATE));
+    }
+}
@@ -1,6 +1,7 @@
 use {
     super::process::Process,
     super::*,
+    crate::ipc::CallFuture,
     crate::object::*,
     alloc::{boxed::Box, sync::Arc},
     bitflags::bitflags,
@@ -9,7 +10,9 @@ use {
         ops::Deref,
         pin::Pin,
         task::{Context, Poll, Waker},
+        time::Duration,
     },
+    futures::future::{select, Either},
     spin::Mutex,
     trapframe::UserContext,
 };
@@ -28,6 +31,20 @@ impl_kobject!(Thread
     fn related_koid(&self) -> KoID {
         self.proc.id()
     }
+    fn get_info(&self, topic: Topic) -> ZxResult<ObjectInfo> {
+        match topic {
+            Topic::Thread => Ok(ObjectInfo::single(self.get_thread_info())),
+            Topic::TaskRuntime => Ok(ObjectInfo::single(self.get_task_runtime())),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+    fn set_property(&self, property: Property, value: usize) -> ZxResult {
+        match property {
+            Property::RegisterFs => self.set_fsbase(value),
+            Property::RegisterGs => self.set_gsbase(value),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
 );
 
 #[derive(Default)]
@@ -40,6 +57,8 @@ struct ThreadInner {
     suspend_count: usize,
     /// The waker of task when suspending.
     waker: Option<Waker>,
+    /// The waker of the blocking syscall, woken when the thread is killed or suspended.
+    blocking_waker: Option<Waker>,
     /// Thread state
     ///
     /// NOTE: This variable will never be `Suspended`. On suspended, the
@@ -52,6 +71,8 @@ struct ThreadInner {
     /// The time this thread has run on cpu
     time: u128,
     flags: ThreadFlag,
+    /// The reply of `channel_call` which was interrupted before it arrived
+    pending_call: Option<CallFuture>,
 }
 
 impl ThreadInner {
@@ -70,8 +91,19 @@ impl ThreadInner {
     }
 
     /// Change state and update signal.
-    fn change_state(&mut self, state: ThreadState) {
+    fn change_state(&mut self, state: ThreadState, base: &KObjectBase) {
         self.state = state;
+        self.update_signal(base);
+    }
+
+    /// Update `THREAD_RUNNING` and `THREAD_SUSPENDED` signals by the current state.
+    fn update_signal(&self, base: &KObjectBase) {
+        let signal = match self.state() {
+            ThreadState::New | ThreadState::Dying | ThreadState::Dead => Signal::empty(),
+            ThreadState::Suspended => Signal::THREAD_SUSPENDED,
+            _ => Signal::THREAD_RUNNING,
+        };
+        base.signal_change(Signal::THREAD_RUNNING | Signal::THREAD_SUSPENDED, signal);
     }
 }
 
@@ -90,14 +122,14 @@ pub type ThreadFn = fn(thread: CurrentThread) -> Pin<Box<dyn Future<Output = ()>
 impl Thread {
     /// Create a new thread.
     pub fn create(proc: &Arc<Process>, name: &str) -> ZxResult<Arc<Self>> {
-        let thread = Arc::new(Thread {
+        let thread = register_object(Arc::new(Thread {
             base: KObjectBase::with_name(name),
             proc: proc.clone(),
             inner: Mutex::new(ThreadInner {
                 context: Some(Box::new(UserContext::default())),
                 ..Default::default()
             }),
-        });
+        }));
         proc.add_thread(thread.clone())?;
         Ok(thread)
     }
@@ -108,6 +140,8 @@ impl Thread {
     }
 
     /// Start execution on the thread.
+    ///
+    /// Return `BAD_STATE` if the thread has already been started.
     pub fn start(
         self: &Arc<Self>,
         entry: usize,
@@ -118,13 +152,16 @@ impl Thread {
     ) -> ZxResult {
         {
             let mut inner = self.inner.lock();
+            if inner.state != ThreadState::New {
+                return Err(ZxError::BAD_STATE);
+            }
             let context = inner.context.as_mut().ok_or(ZxError::BAD_STATE)?;
             context.general.rip = entry;
             context.general.rsp = stack;
             context.general.rdi = arg1;
             context.general.rsi = arg2;
             context.general.rflags |= 0x3202;
-            inner.change_state(ThreadState::Running);
+            inner.change_state(ThreadState::Running, &self.base);
         }
         kernel_hal::Thread::spawn(thread_fn(CurrentThread(self.clone())), 0);
         Ok(())
@@ -145,10 +182,13 @@ impl Thread {
         if inner.state == ThreadState::Dying {
             return;
         }
-        inner.change_state(ThreadState::Dying);
+        inner.change_state(ThreadState::Dying, &self.base);
         if let Some(waker) = inner.waker.take() {
             waker.wake();
         }
+        if let Some(waker) = inner.blocking_waker.take() {
+            waker.wake();
+        }
     }
 
     /// Read one aspect of thread state.
@@ -178,6 +218,16 @@ impl Thread {
         let inner = self.inner.lock();
         ThreadInfo {
             state: inner.state() as u32,
+            wait_exception_channel_type: 0,
+            cpu_affinity_mask: [0; 8],
+        }
+    }
+
+    /// Get the runtime of this thread.
+    pub fn get_task_runtime(&self) -> TaskRuntimeInfo {
+        TaskRuntimeInfo {
+            cpu_time: self.get_time() as i64,
+            queue_time: 0,
         }
     }
     /// Get the thread state.
@@ -230,6 +280,16 @@ impl Thread {
         context.general.gsbase = gsbase;
         Ok(())
     }
+
+    /// Save the reply of an interrupted `channel_call` to be finished later.
+    pub fn set_pending_call(&self, reply: CallFuture) {
+        self.inner.lock().pending_call = Some(reply);
+    }
+
+    /// Take the reply of the interrupted `channel_call`.
+    pub fn take_pending_call(&self) -> Option<CallFuture> {
+        self.inner.lock().pending_call.take()
+    }
 }
 
 impl Task for Thread {
@@ -240,8 +300,10 @@ impl Task for Thread {
     fn suspend(&self) {
         let mut inner = self.inner.lock();
         inner.suspend_count += 1;
-        // let state = inner.state;
-        // inner.change_state(state);
+        inner.update_signal(&self.base);
+        if let Some(waker) = inner.blocking_waker.take() {
+            waker.wake();
+        }
     }
 
     fn resume(&self) {
@@ -249,8 +311,7 @@ impl Task for Thread {
         assert_ne!(inner.suspend_count, 0);
         inner.suspend_count -= 1;
         if inner.suspend_count == 0 {
-            // let state = inner.state;
-            // inner.change_state(state);
+            inner.update_signal(&self.base);
             if let Some(waker) = inner.waker.take() {
                 waker.wake();
             }
@@ -281,7 +342,9 @@ impl Drop for CurrentThread {
     /// Terminate the current running thread.
     fn drop(&mut self) {
         let mut inner = self.inner.lock();
-        inner.change_state(ThreadState::Dead);
+        inner.change_state(ThreadState::Dead, &self.base);
+        drop(inner);
+        self.base.signal_set(Signal::THREAD_TERMINATED);
         self.proc().remove_thread(self.base.id);
     }
 }
@@ -328,8 +391,8 @@ impl CurrentThread {
     pub fn end_running(&self, context: Box<UserContext>) {
         let mut inner = self.inner.lock();
         inner.context = Some(context);
-        // let state = inner.state;
-        // inner.change_state(state);
+        // the thread may have been suspended while running
+        inner.update_signal(&self.base);
     }
 
     /// Access saved context of current thread.
@@ -343,6 +406,71 @@ impl CurrentThread {
         let mut cx = inner.context.as_mut().unwrap();
         f(&mut cx)
     }
+
+    /// Run a blocking `future` on behalf of the current thread.
+    ///
+    /// The thread is put into the blocked `state` until the future completes,
+    /// or returns `TIMED_OUT` if the `deadline` passed before that.
+    ///
+    /// The blocking is interrupted with `INTERNAL_INTR_KILLED` if the thread is killed,
+    /// or `INTERNAL_INTR_RETRY` if it is suspended, in which case the syscall should be
+    /// retried after the thread is resumed.
+    pub async fn blocking_run<F, T>(
+        &self,
+        future: F,
+        state: ThreadState,
+        deadline: Duration,
+    ) -> ZxResult<T>
+    where
+        F: Future<Output = T> + Unpin,
+    {
+        let old_state = {
+            let mut inner = self.inner.lock();
+            let old_state = inner.state;
+            inner.change_state(state, &self.base);
+            old_state
+        };
+        let interrupted = select(self.interrupted(), kernel_hal::sleep_until(deadline));
+        let ret = match select(future, interrupted).await {
+            Either::Left((ret, _)) => Ok(ret),
+            Either::Right((Either::Left((err, _)), _)) => Err(err),
+            Either::Right((Either::Right(_), _)) => Err(ZxError::TIMED_OUT),
+        };
+        let mut inner = self.inner.lock();
+        inner.blocking_waker = None;
+        // the thread may be killed while blocking
+        if inner.state == state {
+            inner.change_state(old_state, &self.base);
+        }
+        ret
+    }
+
+    /// Wait until the thread is killed or suspended,
+    /// and return the error to interrupt the blocking syscall.
+    fn interrupted(&self) -> impl Future<Output = ZxError> {
+        #[must_use = "interrupted does nothing unless polled/`await`-ed"]
+        struct InterruptChecker {
+            thread: Arc<Thread>,
+        }
+        impl Future for InterruptChecker {
+            type Output = ZxError;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+                let mut inner = self.thread.inner.lock();
+                if inner.state == ThreadState::Dying || inner.state == ThreadState::Dead {
+                    Poll::Ready(ZxError::INTERNAL_INTR_KILLED)
+                } else if inner.suspend_count != 0 {
+                    Poll::Ready(ZxError::INTERNAL_INTR_RETRY)
+                } else {
+                    inner.blocking_waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+        InterruptChecker {
+            thread: self.0.clone(),
+        }
+    }
 }
 
 /// The thread state.
@@ -390,6 +518,35 @@ impl Default for ThreadState {
 #[repr(C)]
 pub struct ThreadInfo {
     state: u32,
+    /// The type of the exception channel the thread is waiting on, if any.
+    wait_exception_channel_type: u32,
+    /// The CPUs the thread may run on.
+    cpu_affinity_mask: [u64; 8],
+}
+
+/// Start a thread of `proc` blocked on an event which is never signaled,
+/// and expect the blocking to be interrupted by killing it.
+#[cfg(test)]
+pub(crate) async fn start_blocked_thread(proc: &Arc<Process>) -> Arc<Thread> {
+    async fn new_thread(thread: CurrentThread) {
+        let cx = thread.wait_for_run().await;
+        thread.end_running(cx);
+        let event: Arc<dyn KernelObject> = crate::signal::Event::new();
+        let future = Box::pin(event.wait_signal(Signal::USER_SIGNAL_0));
+        let deadline = Duration::from_nanos(i64::MAX as u64);
+        let ret = thread
+            .blocking_run(future, ThreadState::BlockedWaitOne, deadline)
+            .await;
+        assert_eq!(ret.err(), Some(ZxError::INTERNAL_INTR_KILLED));
+    }
+    let thread = Thread::create(proc, "blocked").expect("failed to create thread");
+    thread
+        .start(0, 0, 0, 0, |thread| Box::pin(new_thread(thread)))
+        .unwrap();
+    while thread.state() != ThreadState::BlockedWaitOne {
+        async_std::task::yield_now().await;
+    }
+    thread
 }
 
 #[cfg(test)]
@@ -452,6 +609,11 @@ mod tests {
             Err(ZxError::BAD_STATE)
         );
 
+        assert_eq!(
+            thread.start(1, 4, 3, 2, |thread| Box::pin(new_thread(thread))),
+            Err(ZxError::BAD_STATE)
+        );
+
         // start another thread should fail
         assert_eq!(
             proc.start(&thread1, 1, 4, Some(handle.clone()), 2, |thread| Box::pin(
@@ -549,9 +711,104 @@ mod tests {
             let _context = thread.wait_for_run().await;
             assert!(timer_now() - time >= Duration::from_millis(20));
         }
-        // FIX ME
-        // let thread: Arc<dyn KernelObject> = thread;
-        // thread.wait_signal(Signal::THREAD_TERMINATED).await;
+
+        let thread: Arc<dyn KernelObject> = thread;
+        thread.wait_signal(Signal::THREAD_TERMINATED).await;
+    }
+
+    #[async_std::test]
+    async fn blocking_run() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let thread = Thread::create(&proc, "thread").expect("failed to create thread");
+        let current_thread = CurrentThread(thread.clone());
+        thread
+            .inner
+            .lock()
+            .change_state(ThreadState::Running, &thread.base);
+
+        // the future completes before the deadline
+        let deadline = timer_now() + Duration::from_millis(100);
+        let ret = current_thread
+            .blocking_run(Box::pin(async { 1 }), ThreadState::BlockedWaitOne, deadline)
+            .await;
+        assert_eq!(ret, Ok(1));
+        assert_eq!(thread.state(), ThreadState::Running);
+
+        // the deadline passes while blocking
+        let deadline = timer_now() + Duration::from_millis(10);
+        let future = Box::pin(async {
+            assert_eq!(thread.state(), ThreadState::BlockedWaitOne);
+            futures::future::pending::<()>().await
+        });
+        let ret = current_thread
+            .blocking_run(future, ThreadState::BlockedWaitOne, deadline)
+            .await;
+        assert_eq!(ret, Err(ZxError::TIMED_OUT));
+        assert!(timer_now() >= deadline);
+        assert_eq!(thread.state(), ThreadState::Running);
+    }
+
+    #[async_std::test]
+    async fn kill_blocked() {
+        kernel_hal_unix::init();
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let thread = start_blocked_thread(&proc).await;
+
+        thread.kill();
+        let proc: Arc<dyn KernelObject> = proc;
+        proc.wait_signal(Signal::PROCESS_TERMINATED).await;
+        assert_eq!(thread.state(), ThreadState::Dead);
+    }
+
+    #[async_std::test]
+    async fn suspend_blocked_call() {
+        let root_job = Job::root();
+        let proc = Process::create(&root_job, "proc").expect("failed to create process");
+        let thread = Thread::create(&proc, "thread").expect("failed to create thread");
+        let current_thread = CurrentThread(thread.clone());
+        thread
+            .inner
+            .lock()
+            .change_state(ThreadState::Running, &thread.base);
+        let (channel0, channel1) = crate::ipc::Channel::create();
+        let msg = |data: &[u8]| crate::ipc::MessagePacket {
+            data: data.to_vec(),
+            handles: vec![],
+        };
+        let deadline = Duration::from_nanos(i64::MAX as u64);
+
+        // the call is interrupted when the thread is suspended
+        let mut reply = channel0.call(msg(b"txid0001")).unwrap();
+        let task = async_std::task::spawn(async move {
+            let ret = current_thread
+                .blocking_run(&mut reply, ThreadState::BlockedChannel, deadline)
+                .await;
+            assert_eq!(ret.err(), Some(ZxError::INTERNAL_INTR_RETRY));
+            current_thread.set_pending_call(reply);
+            current_thread
+        });
+        while thread.state() != ThreadState::BlockedChannel {
+            async_std::task::yield_now().await;
+        }
+        thread.suspend();
+        let current_thread = task.await;
+        assert_eq!(thread.state(), ThreadState::Suspended);
+        assert!(thread.signal().contains(Signal::THREAD_SUSPENDED));
+
+        // the reply arrived during suspension is kept for the retry
+        let request = channel1.read().unwrap();
+        let mut response = msg(b"txid0002");
+        response.set_txid(request.get_txid());
+        channel1.write(response).unwrap();
+        thread.resume();
+        let reply = current_thread.take_pending_call().unwrap();
+        let ret = current_thread
+            .blocking_run(reply, ThreadState::BlockedChannel, deadline)
+            .await;
+        assert_eq!(&ret.unwrap().unwrap().data[4..], b"0002");
+        assert!(current_thread.take_pending_call().is_none());
     }
 
     #[test]
@@ -0,0 +1,41 @@
+//! Kernel counters.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A named kernel counter.
+///
+/// Declared as a `static` next to the code it measures.
+pub struct KCounter {
+    name: &'static str,
+    value: AtomicUsize,
+}
+
+impl KCounter {
+    /// Create a counter with initial value 0.
+    pub const fn new(name: &'static str) -> Self {
+        KCounter {
+            name,
+            value: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get the name of the counter.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get the current value.
+    pub fn get(&self) -> usize {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    /// Add `count` to the counter.
+    pub fn add(&self, count: usize) {
+        self.value.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Raise the counter to `value` if it is larger, used as a high-water mark.
+    pub fn update_max(&self, value: usize) {
+        self.value.fetch_max(value, Ordering::Relaxed);
+    }
+}
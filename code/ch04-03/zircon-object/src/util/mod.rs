@@ -2,3 +2,4 @@
 
 pub(crate) mod block_range;
 pub mod elf_loader;
+pub mod kcounter;
@@ -54,7 +54,14 @@ pub struct VmAddressRegion {
     inner: Mutex<Option<VmarInner>>,
 }
 
-impl_kobject!(VmAddressRegion);
+impl_kobject!(VmAddressRegion
+    fn get_info(&self, topic: Topic) -> ZxResult<ObjectInfo> {
+        match topic {
+            Topic::Vmar => Ok(ObjectInfo::single(self.get_info())),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+);
 
 /// The mutable part of `VmAddressRegion`.
 #[derive(Default)]
@@ -72,7 +79,7 @@ impl VmAddressRegion {
             let i = VMAR_ID.fetch_add(1, Ordering::SeqCst);
             (0x2_0000_0000 + 0x100_0000_0000 * i, 0x100_0000_0000)
         };
-        Arc::new(VmAddressRegion {
+        register_object(Arc::new(VmAddressRegion {
             flags: VmarFlags::ROOT_FLAGS,
             base: KObjectBase::new(),
             addr,
@@ -80,14 +87,14 @@ impl VmAddressRegion {
             parent: None,
             page_table: Arc::new(Mutex::new(kernel_hal::PageTable::new())), //hal PageTable
             inner: Mutex::new(Some(VmarInner::default())),
-        })
+        }))
     }
 
     /// Create a kernel root VMAR.
     pub fn new_kernel() -> Arc<Self> {
         let kernel_vmar_base = KERNEL_ASPACE_BASE as usize;
         let kernel_vmar_size = KERNEL_ASPACE_SIZE as usize;
-        Arc::new(VmAddressRegion {
+        register_object(Arc::new(VmAddressRegion {
             flags: VmarFlags::ROOT_FLAGS,
             base: KObjectBase::new(),
             addr: kernel_vmar_base,
@@ -95,7 +102,7 @@ impl VmAddressRegion {
             parent: None,
             page_table: Arc::new(Mutex::new(kernel_hal::PageTable::new())),
             inner: Mutex::new(Some(VmarInner::default())),
-        })
+        }))
     }
 
     /// Create a child VMAR at the `offset`.
@@ -120,7 +127,7 @@ impl VmAddressRegion {
         let mut guard = self.inner.lock();
         let inner = guard.as_mut().ok_or(ZxError::BAD_STATE)?;
         let offset = self.determine_offset(inner, offset, len, align)?;
-        let child = Arc::new(VmAddressRegion {
+        let child = register_object(Arc::new(VmAddressRegion {
             flags,
             base: KObjectBase::new(),
             addr: self.addr + offset,
@@ -128,7 +135,7 @@ impl VmAddressRegion {
             parent: Some(self.clone()),
             page_table: self.page_table.clone(),
             inner: Mutex::new(Some(VmarInner::default())),
-        });
+        }));
         inner.children.push(child.clone());
         Ok(child)
     }
@@ -106,7 +106,25 @@ pub struct VmObject {
     inner: Mutex<VmObjectInner>,
 }
 
-impl_kobject!(VmObject);
+impl_kobject!(VmObject
+    fn get_property(&self, property: Property) -> ZxResult<usize> {
+        match property {
+            Property::VmoContentSize => Ok(self.content_size()),
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+    fn set_property(&self, property: Property, value: usize) -> ZxResult {
+        match property {
+            Property::VmoContentSize => {
+                if value > self.len() {
+                    return Err(ZxError::OUT_OF_RANGE);
+                }
+                self.set_content_size(value)
+            }
+            _ => Err(ZxError::WRONG_TYPE),
+        }
+    }
+);
 
 #[derive(Default)]
 struct VmObjectInner {
@@ -124,33 +142,33 @@ impl VmObject {
 
     /// Create a new VMO, which can be resizable, backing on physical memory allocated in pages.
     pub fn new_paged_with_resizable(resizable: bool, pages: usize) -> Arc<Self> {
-        let base = KObjectBase::new();
-        Arc::new(VmObject {
+        let base = KObjectBase::with_signal(Signal::VMO_ZERO_CHILDREN);
+        register_object(Arc::new(VmObject {
             resizable,
             trait_: VMObjectPaged::new(pages),
             inner: Mutex::new(VmObjectInner::default()),
             base,
-        })
+        }))
     }
 
     /// Create a new VMO representing a piece of contiguous physical memory.
     pub fn new_physical(paddr: PhysAddr, pages: usize) -> Arc<Self> {
-        Arc::new(VmObject {
-            base: KObjectBase::new(),
+        register_object(Arc::new(VmObject {
+            base: KObjectBase::with_signal(Signal::VMO_ZERO_CHILDREN),
             resizable: false,
             trait_: VMObjectPhysical::new(paddr, pages),
             inner: Mutex::new(VmObjectInner::default()),
-        })
+        }))
     }
 
     /// Create a VM object referring to a specific contiguous range of physical frame.  
     pub fn new_contiguous(pages: usize, align_log2: usize) -> ZxResult<Arc<Self>> {
-        let vmo = Arc::new(VmObject {
-            base: KObjectBase::new(),
+        let vmo = register_object(Arc::new(VmObject {
+            base: KObjectBase::with_signal(Signal::VMO_ZERO_CHILDREN),
             resizable: false,
             trait_: VMObjectPaged::new_contiguous(pages, align_log2)?,
             inner: Mutex::new(VmObjectInner::default()),
-        });
+        }));
         Ok(vmo)
     }
 
@@ -162,8 +180,9 @@ impl VmObject {
         len: usize,
     ) -> ZxResult<Arc<Self>> {
         let base = KObjectBase::with_name(&self.base.name());
+        base.signal_set(Signal::VMO_ZERO_CHILDREN);
         let trait_ = self.trait_.create_child(offset, len)?;
-        let child = Arc::new(VmObject {
+        let child = register_object(Arc::new(VmObject {
             base,
             resizable,
             trait_,
@@ -171,7 +190,7 @@ impl VmObject {
                 parent: Arc::downgrade(self),
                 ..VmObjectInner::default()
             }),
-        });
+        }));
         self.add_child(&child);
         Ok(child)
     }
@@ -197,15 +216,17 @@ impl VmObject {
         if self.trait_.cache_policy() != CachePolicy::Cached && !self.trait_.is_contiguous() {
             return Err(ZxError::BAD_STATE);
         }
-        let child = Arc::new(VmObject {
-            base: KObjectBase::with_name(&self.base.name()),
+        let base = KObjectBase::with_name(&self.base.name());
+        base.signal_set(Signal::VMO_ZERO_CHILDREN);
+        let child = register_object(Arc::new(VmObject {
+            base,
             resizable: false,
             trait_: VMObjectSlice::new(self.trait_.clone(), offset, size),
             inner: Mutex::new(VmObjectInner {
                 parent: Arc::downgrade(self),
                 ..VmObjectInner::default()
             }),
-        });
+        }));
         self.add_child(&child);
         Ok(child)
     }
@@ -216,9 +237,9 @@ impl VmObject {
         let mut inner = self.inner.lock();
         inner.children.retain(|x| x.strong_count() != 0);
         inner.children.push(Arc::downgrade(child));
-        // if inner.children.len() == 1 {
-        //     self.base.signal_clear(Signal::VMO_ZERO_CHILDREN);
-        // }
+        if inner.children.len() == 1 {
+            self.base.signal_clear(Signal::VMO_ZERO_CHILDREN);
+        }
     }
 
     /// Set the length of this VMO if resizable.
@@ -372,6 +393,9 @@ impl Drop for VmObject {
             let mut inner = child.inner.lock();
             inner.children.retain(|c| c.strong_count() != 0);
         }
+        if children.is_empty() {
+            parent.base.signal_set(Signal::VMO_ZERO_CHILDREN);
+        }
     }
 }
 
@@ -466,4 +490,17 @@ mod tests {
         vmo.read(0, &mut buf).unwrap();
         assert_eq!(&buf, &[0, 1, 2, 3]);
     }
+
+    #[test]
+    fn zero_children_signal() {
+        let vmo = VmObject::new_paged(1);
+        assert!(vmo.signal().contains(Signal::VMO_ZERO_CHILDREN));
+
+        let child = vmo.create_child(false, 0, PAGE_SIZE).unwrap();
+        assert!(!vmo.signal().contains(Signal::VMO_ZERO_CHILDREN));
+        assert!(child.signal().contains(Signal::VMO_ZERO_CHILDREN));
+
+        drop(child);
+        assert!(vmo.signal().contains(Signal::VMO_ZERO_CHILDREN));
+    }
 }
@@ -1,11 +1,18 @@
 use {
     super::*,
-    zircon_object::ipc::{Channel, MessagePacket},
+    crate::handle::reduce_rights,
+    alloc::vec::Vec,
+    zircon_object::{
+        ipc::{CallFuture, Channel, MessagePacket},
+        task::{PolicyCondition, Process, ThreadState},
+    },
 };
 
 impl Syscall<'_> {
     #[allow(clippy::too_many_arguments)]
-    /// Read/Receive a message from a channel.  
+    /// Read/Receive a message from a channel.
+    ///
+    /// If `is_etc`, `handles` is an array of `HandleInfo` instead of `HandleValue`.
     pub fn sys_channel_read(
         &self,
         handle_value: HandleValue,
@@ -16,33 +23,35 @@ impl Syscall<'_> {
         num_handles: u32,
         mut actual_bytes: UserOutPtr<u32>,
         mut actual_handles: UserOutPtr<u32>,
+        is_etc: bool,
     ) -> ZxResult {
         info!(
             "channel.read: handle={:#x?}, options={:?}, bytes=({:#x?}; {:#x?}), handles=({:#x?}; {:#x?})",
             handle_value, options, bytes, num_bytes, handles, num_handles,
         );
+        if options & !CHANNEL_READ_MAY_DISCARD != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
         let proc = self.thread.proc();
         let channel = proc.get_object_with_rights::<Channel>(handle_value, Rights::READ)?;
-        // FIX ME:
-        const MAY_DISCARD: u32 = 1;
-        let never_discard = options & MAY_DISCARD == 0;
-
-        let msg = if never_discard {
-            channel.check_and_read(|front_msg| {
-                if num_bytes < front_msg.data.len() as u32
-                    || num_handles < front_msg.handles.len() as u32
-                {
-                    let bytes = front_msg.data.len();
-                    actual_bytes.write_if_not_null(bytes as u32)?;
-                    actual_handles.write_if_not_null(front_msg.handles.len() as u32)?;
-                    Err(ZxError::BUFFER_TOO_SMALL)
-                } else {
-                    Ok(())
+
+        // without `MAY_DISCARD`, a message too large is kept in the channel,
+        // otherwise it is dequeued and dropped together with its handles.
+        let msg = channel.check_and_read(|front_msg| {
+            if num_bytes < front_msg.data.len() as u32
+                || num_handles < front_msg.handles.len() as u32
+            {
+                if options & CHANNEL_READ_MAY_DISCARD != 0 {
+                    return Ok(());
                 }
-            })?
-        } else {
-            channel.read()?
-        };
+                let bytes = front_msg.data.len();
+                actual_bytes.write_if_not_null(bytes as u32)?;
+                actual_handles.write_if_not_null(front_msg.handles.len() as u32)?;
+                return Err(ZxError::BUFFER_TOO_SMALL);
+            }
+            // a message whose handles can not be received is kept in the channel
+            proc.check_handle_room(front_msg.handles.len())
+        })?;
 
         // 如果要过 core-tests 把这个打开
         // hack_core_tests(handle_value, &self.thread.proc().name(), &mut msg.data);
@@ -53,8 +62,18 @@ impl Syscall<'_> {
             return Err(ZxError::BUFFER_TOO_SMALL);
         }
         bytes.write_array(msg.data.as_slice())?;
-        let values = proc.add_handles(msg.handles);
-        UserOutPtr::<HandleValue>::from(handles).write_array(&values)?;
+        if is_etc {
+            let mut handle_infos: Vec<HandleInfo> =
+                msg.handles.iter().map(|h| h.get_handle_info()).collect();
+            let values = proc.add_handles(msg.handles)?;
+            for (info, value) in handle_infos.iter_mut().zip(values) {
+                info.handle = value;
+            }
+            UserOutPtr::<HandleInfo>::from(handles).write_array(&handle_infos)?;
+        } else {
+            let values = proc.add_handles(msg.handles)?;
+            UserOutPtr::<HandleValue>::from(handles).write_array(&values)?;
+        }
         Ok(())
     }
 
@@ -75,6 +94,175 @@ impl Syscall<'_> {
         if options != 0 {
             return Err(ZxError::INVALID_ARGS);
         }
+        let msg = self.read_user_message(
+            handle_value,
+            &user_bytes,
+            num_bytes,
+            &user_handles,
+            num_handles,
+        )?;
+        let proc = self.thread.proc();
+        let channel = proc.get_object_with_rights::<Channel>(handle_value, Rights::WRITE)?;
+        channel.write(msg)?;
+        Ok(())
+    }
+
+    /// Write a message to a channel, with handle dispositions.
+    ///
+    /// Each handle is moved or duplicated into the message with its type checked
+    /// and rights reduced. The result of each handle is written back.
+    pub fn sys_channel_write_etc(
+        &self,
+        handle_value: HandleValue,
+        options: u32,
+        user_bytes: UserInPtr<u8>,
+        num_bytes: u32,
+        mut user_handles: UserInOutPtr<HandleDisposition>,
+        num_handles: u32,
+    ) -> ZxResult {
+        info!(
+            "channel.write_etc: handle_value={:#x}, num_bytes={:#x}, num_handles={:#x}",
+            handle_value, num_bytes, num_handles,
+        );
+        if options != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        if num_bytes > 65536 || num_handles > 64 {
+            return Err(ZxError::OUT_OF_RANGE);
+        }
+        let proc = self.thread.proc();
+        let data = user_bytes.read_array(num_bytes as usize)?;
+        let mut dispositions = user_handles.read_array(num_handles as usize)?;
+        let mut handles = Vec::with_capacity(dispositions.len());
+        let mut ret = Ok(());
+        for disposition in dispositions.iter_mut() {
+            match handle_from_disposition(proc, handle_value, disposition) {
+                Ok(handle) => {
+                    disposition.result = ZxError::OK as i32;
+                    handles.push(handle);
+                }
+                Err(err) => {
+                    disposition.result = err as i32;
+                    if ret.is_ok() {
+                        ret = Err(err);
+                    }
+                }
+            }
+        }
+        user_handles.write_array(&dispositions)?;
+        ret?;
+        let channel = proc.get_object_with_rights::<Channel>(handle_value, Rights::WRITE)?;
+        channel.write(MessagePacket { data, handles })?;
+        Ok(())
+    }
+
+    /// Send a message to a channel and await a reply.
+    ///
+    /// If the call is interrupted, `INTERNAL_INTR_RETRY` is returned
+    /// and the reply should be waited by `sys_channel_call_finish`.
+    pub async fn sys_channel_call_noretry(
+        &self,
+        handle_value: HandleValue,
+        options: u32,
+        deadline: Deadline,
+        user_args: UserInPtr<ChannelCallArgs>,
+        actual_bytes: UserOutPtr<u32>,
+        actual_handles: UserOutPtr<u32>,
+    ) -> ZxResult {
+        let args = user_args.read()?;
+        info!(
+            "channel.call_noretry: handle={:#x}, deadline={:?}, args={:#x?}",
+            handle_value, deadline, args
+        );
+        if options != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        if args.wr_num_bytes < 4 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let msg = self.read_user_message(
+            handle_value,
+            &args.wr_bytes,
+            args.wr_num_bytes,
+            &args.wr_handles,
+            args.wr_num_handles,
+        )?;
+        let proc = self.thread.proc();
+        let channel =
+            proc.get_object_with_rights::<Channel>(handle_value, Rights::READ | Rights::WRITE)?;
+        // the reply can not be put back once received,
+        // so make sure that the handles it may carry can be received
+        proc.check_handle_room(args.rd_num_handles.min(64) as usize)?;
+        let reply = channel.call(msg)?;
+        self.wait_call_reply(reply, deadline, args, actual_bytes, actual_handles)
+            .await
+    }
+
+    /// Finish the `channel_call` interrupted by `INTERNAL_INTR_RETRY`.
+    pub async fn sys_channel_call_finish(
+        &self,
+        deadline: Deadline,
+        user_args: UserInPtr<ChannelCallArgs>,
+        actual_bytes: UserOutPtr<u32>,
+        actual_handles: UserOutPtr<u32>,
+    ) -> ZxResult {
+        let args = user_args.read()?;
+        info!(
+            "channel.call_finish: deadline={:?}, args={:#x?}",
+            deadline, args
+        );
+        let reply = self.thread.take_pending_call().ok_or(ZxError::BAD_STATE)?;
+        self.wait_call_reply(reply, deadline, args, actual_bytes, actual_handles)
+            .await
+    }
+
+    /// Wait for the `reply` of `channel_call` and write it to the buffers in `args`.
+    async fn wait_call_reply(
+        &self,
+        mut reply: CallFuture,
+        deadline: Deadline,
+        args: ChannelCallArgs,
+        mut actual_bytes: UserOutPtr<u32>,
+        mut actual_handles: UserOutPtr<u32>,
+    ) -> ZxResult {
+        let msg = match self
+            .thread
+            .blocking_run(&mut reply, ThreadState::BlockedChannel, deadline.into())
+            .await
+        {
+            Ok(msg) => msg?,
+            Err(ZxError::INTERNAL_INTR_RETRY) => {
+                self.thread.set_pending_call(reply);
+                return Err(ZxError::INTERNAL_INTR_RETRY);
+            }
+            Err(err) => return Err(err),
+        };
+        actual_bytes.write_if_not_null(msg.data.len() as u32)?;
+        actual_handles.write_if_not_null(msg.handles.len() as u32)?;
+        if args.rd_num_bytes < msg.data.len() as u32
+            || args.rd_num_handles < msg.handles.len() as u32
+        {
+            return Err(ZxError::BUFFER_TOO_SMALL);
+        }
+        let mut rd_bytes = args.rd_bytes;
+        let mut rd_handles = args.rd_handles;
+        rd_bytes.write_array(msg.data.as_slice())?;
+        let values = self.thread.proc().add_handles(msg.handles)?;
+        rd_handles.write_array(&values)?;
+        Ok(())
+    }
+
+    /// Read a message to be written to the channel of `handle_value` from user buffers.
+    ///
+    /// The handles are removed from the process, even if an error is returned.
+    fn read_user_message(
+        &self,
+        handle_value: HandleValue,
+        user_bytes: &UserInPtr<u8>,
+        num_bytes: u32,
+        user_handles: &UserInPtr<HandleValue>,
+        num_handles: u32,
+    ) -> ZxResult<MessagePacket> {
         if num_bytes > 65536 {
             return Err(ZxError::OUT_OF_RANGE);
         }
@@ -94,9 +282,7 @@ impl Syscall<'_> {
                 return Err(ZxError::ACCESS_DENIED);
             }
         }
-        let channel = proc.get_object_with_rights::<Channel>(handle_value, Rights::WRITE)?;
-        channel.write(MessagePacket { data, handles })?;
-        Ok(())
+        Ok(MessagePacket { data, handles })
     }
 
     /// Create a new channel.   
@@ -111,15 +297,85 @@ impl Syscall<'_> {
             return Err(ZxError::INVALID_ARGS);
         }
         let proc = self.thread.proc();
+        proc.check_policy(PolicyCondition::NewChannel)?;
         let (end0, end1) = Channel::create();
-        let handle0 = proc.add_handle(Handle::new(end0, Rights::DEFAULT_CHANNEL));
-        let handle1 = proc.add_handle(Handle::new(end1, Rights::DEFAULT_CHANNEL));
+        let handle0 = proc.add_handle(Handle::new(end0, Rights::DEFAULT_CHANNEL))?;
+        let handle1 = proc.add_handle(Handle::new(end1, Rights::DEFAULT_CHANNEL))?;
         out0.write(handle0)?;
         out1.write(handle1)?;
         Ok(())
     }
 }
 
+/// `zx_channel_call_args_t`
+#[repr(C)]
+#[derive(Debug)]
+pub struct ChannelCallArgs {
+    wr_bytes: UserInPtr<u8>,
+    wr_handles: UserInPtr<HandleValue>,
+    rd_bytes: UserOutPtr<u8>,
+    rd_handles: UserOutPtr<HandleValue>,
+    wr_num_bytes: u32,
+    wr_num_handles: u32,
+    rd_num_bytes: u32,
+    rd_num_handles: u32,
+}
+
+/// Take a handle out of the process as described by `disposition`.
+///
+/// The handle to be moved is always removed from the process, even on failure.
+fn handle_from_disposition(
+    proc: &Process,
+    channel_handle: HandleValue,
+    disposition: &HandleDisposition,
+) -> ZxResult<Handle> {
+    let mut handle = match disposition.op {
+        HANDLE_OP_MOVE => {
+            let handle = proc.remove_handle(disposition.handle)?;
+            if disposition.handle == channel_handle {
+                return Err(ZxError::NOT_SUPPORTED);
+            }
+            if !handle.rights.contains(Rights::TRANSFER) {
+                return Err(ZxError::ACCESS_DENIED);
+            }
+            handle
+        }
+        HANDLE_OP_DUPLICATE => {
+            let handle = proc.get_handle(disposition.handle)?;
+            if !handle.rights.contains(Rights::DUPLICATE) {
+                return Err(ZxError::ACCESS_DENIED);
+            }
+            handle
+        }
+        _ => return Err(ZxError::INVALID_ARGS),
+    };
+    if disposition.obj_type != ObjectType::None as u32
+        && disposition.obj_type != handle.object.obj_type() as u32
+    {
+        return Err(ZxError::WRONG_TYPE);
+    }
+    let rights = Rights::from_bits(disposition.rights).ok_or(ZxError::INVALID_ARGS)?;
+    handle.rights = reduce_rights(handle.rights, rights)?;
+    Ok(handle)
+}
+
+/// Read the message even if the buffers are too small, and discard it.
+const CHANNEL_READ_MAY_DISCARD: u32 = 1;
+
+const HANDLE_OP_MOVE: u32 = 0;
+const HANDLE_OP_DUPLICATE: u32 = 1;
+
+/// `zx_handle_disposition_t`
+#[repr(C)]
+#[derive(Debug)]
+pub struct HandleDisposition {
+    op: u32,
+    handle: HandleValue,
+    obj_type: u32,
+    rights: u32,
+    result: i32,
+}
+
 // HACK: pass arguments to standalone-test
 // #[allow(clippy::naive_bytecount)]
 // fn hack_core_tests(handle: HandleValue, thread_name: &str, data: &mut Vec<u8>) {
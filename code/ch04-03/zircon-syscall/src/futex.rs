@@ -0,0 +1,134 @@
+use {
+    super::*,
+    alloc::sync::Arc,
+    core::sync::atomic::AtomicI32,
+    zircon_object::{signal::Futex, task::*},
+};
+
+impl Syscall<'_> {
+    /// Wait on a futex.
+    ///
+    /// This atomically verifies that `value_ptr` still contains the value `current_value`
+    /// and sleeps until the futex is made available by a call to `zx_futex_wake`.
+    pub async fn sys_futex_wait(
+        &self,
+        value_ptr: UserInPtr<AtomicI32>,
+        current_value: i32,
+        new_futex_owner: HandleValue,
+        deadline: Deadline,
+    ) -> ZxResult {
+        info!(
+            "futex.wait: value_ptr={:#x?}, current_value={:#x}, new_futex_owner={:#x}, deadline={:?}",
+            value_ptr, current_value, new_futex_owner, deadline
+        );
+        let futex = self.get_futex(&value_ptr)?;
+        let new_owner = self.get_futex_owner(new_futex_owner)?;
+        if let Some(owner) = &new_owner {
+            if Arc::ptr_eq(owner, self.thread) {
+                return Err(ZxError::INVALID_ARGS);
+            }
+        }
+        let future = futex.wait_with_owner(current_value, Some((*self.thread).clone()), new_owner);
+        self.thread
+            .blocking_run(future, ThreadState::BlockedFutex, deadline.into())
+            .await?
+    }
+
+    /// Wake some number of threads waiting on a futex.
+    pub fn sys_futex_wake(&self, value_ptr: UserInPtr<AtomicI32>, wake_count: u32) -> ZxResult {
+        info!(
+            "futex.wake: value_ptr={:#x?}, wake_count={:#x}",
+            value_ptr, wake_count
+        );
+        let futex = self.get_futex(&value_ptr)?;
+        futex.wake(wake_count as usize);
+        Ok(())
+    }
+
+    /// Wake one thread waiting on a futex, and assign ownership to it.
+    pub fn sys_futex_wake_single_owner(&self, value_ptr: UserInPtr<AtomicI32>) -> ZxResult {
+        info!("futex.wake_single_owner: value_ptr={:#x?}", value_ptr);
+        let futex = self.get_futex(&value_ptr)?;
+        futex.wake_single_owner();
+        Ok(())
+    }
+
+    /// Wake some number of threads waiting on a futex, and move more waiters to another wait queue.
+    pub fn sys_futex_requeue(
+        &self,
+        value_ptr: UserInPtr<AtomicI32>,
+        wake_count: u32,
+        current_value: i32,
+        requeue_ptr: UserInPtr<AtomicI32>,
+        requeue_count: u32,
+        new_requeue_owner: HandleValue,
+    ) -> ZxResult {
+        info!(
+            "futex.requeue: value_ptr={:#x?}, wake_count={:#x}, current_value={:#x}, requeue_ptr={:#x?}, requeue_count={:#x}, new_requeue_owner={:#x}",
+            value_ptr, wake_count, current_value, requeue_ptr, requeue_count, new_requeue_owner
+        );
+        let futex = self.get_futex(&value_ptr)?;
+        let requeue_futex = self.get_futex(&requeue_ptr)?;
+        let new_requeue_owner = self.get_futex_owner(new_requeue_owner)?;
+        futex.requeue(
+            current_value,
+            wake_count as usize,
+            requeue_count as usize,
+            &requeue_futex,
+            new_requeue_owner,
+        )
+    }
+
+    /// Wake one thread waiting on a futex and assign ownership to it,
+    /// then move more waiters to another wait queue.
+    pub fn sys_futex_requeue_single_owner(
+        &self,
+        value_ptr: UserInPtr<AtomicI32>,
+        current_value: i32,
+        requeue_ptr: UserInPtr<AtomicI32>,
+        requeue_count: u32,
+        new_requeue_owner: HandleValue,
+    ) -> ZxResult {
+        info!(
+            "futex.requeue_single_owner: value_ptr={:#x?}, current_value={:#x}, requeue_ptr={:#x?}, requeue_count={:#x}, new_requeue_owner={:#x}",
+            value_ptr, current_value, requeue_ptr, requeue_count, new_requeue_owner
+        );
+        let futex = self.get_futex(&value_ptr)?;
+        let requeue_futex = self.get_futex(&requeue_ptr)?;
+        let new_requeue_owner = self.get_futex_owner(new_requeue_owner)?;
+        futex.requeue_single_owner(
+            current_value,
+            requeue_count as usize,
+            &requeue_futex,
+            new_requeue_owner,
+        )
+    }
+
+    /// Fetch the koid of the current owner of a futex, if any.
+    pub fn sys_futex_get_owner(
+        &self,
+        value_ptr: UserInPtr<AtomicI32>,
+        mut koid: UserOutPtr<KoID>,
+    ) -> ZxResult {
+        info!("futex.get_owner: value_ptr={:#x?}", value_ptr);
+        let futex = self.get_futex(&value_ptr)?;
+        koid.write(futex.owner())?;
+        Ok(())
+    }
+
+    /// Get the futex of the current process on the user value at `value_ptr`.
+    fn get_futex(&self, value_ptr: &UserInPtr<AtomicI32>) -> ZxResult<Arc<Futex>> {
+        value_ptr.check()?;
+        let value = value_ptr.as_ref()?;
+        Ok(self.thread.proc().get_futex(value))
+    }
+
+    /// Get the thread of `handle_value` as a new futex owner, `None` if the handle is invalid.
+    fn get_futex_owner(&self, handle_value: HandleValue) -> ZxResult<Option<Arc<Thread>>> {
+        if handle_value == INVALID_HANDLE {
+            return Ok(None);
+        }
+        let thread = self.thread.proc().get_object::<Thread>(handle_value)?;
+        Ok(Some(thread))
+    }
+}
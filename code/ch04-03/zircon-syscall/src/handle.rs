@@ -0,0 +1,101 @@
+use super::*;
+
+impl Syscall<'_> {
+    /// Close a handle.
+    pub fn sys_handle_close(&self, handle: HandleValue) -> ZxResult {
+        info!("handle.close: handle={:#x?}", handle);
+        if handle == INVALID_HANDLE {
+            return Ok(());
+        }
+        let proc = self.thread.proc();
+        proc.remove_handle(handle)?;
+        Ok(())
+    }
+
+    /// Close a number of handles.
+    ///
+    /// All the valid handles are closed, even if some of them are invalid.
+    pub fn sys_handle_close_many(
+        &self,
+        handles: UserInPtr<HandleValue>,
+        num_handles: usize,
+    ) -> ZxResult {
+        info!(
+            "handle.close_many: handles=({:#x?}; {:#x?})",
+            handles, num_handles,
+        );
+        let proc = self.thread.proc();
+        let handles = handles.read_array(num_handles)?;
+        let mut ret = Ok(());
+        for handle in handles {
+            if handle == INVALID_HANDLE {
+                continue;
+            }
+            if let Err(err) = proc.remove_handle(handle) {
+                ret = Err(err);
+            }
+        }
+        ret
+    }
+
+    /// Creates a duplicate of handle.
+    ///
+    /// Referring to the same underlying object, with new access rights `rights`.
+    pub fn sys_handle_duplicate(
+        &self,
+        handle_value: HandleValue,
+        rights: u32,
+        mut new_handle_value: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        info!(
+            "handle.duplicate: handle={:#x?}, rights={:#x?}",
+            handle_value, rights
+        );
+        let rights = Rights::from_bits(rights).ok_or(ZxError::INVALID_ARGS)?;
+        let proc = self.thread.proc();
+        let new_value = proc.dup_handle_operating_rights(handle_value, |handle_rights| {
+            if !handle_rights.contains(Rights::DUPLICATE) {
+                return Err(ZxError::ACCESS_DENIED);
+            }
+            reduce_rights(handle_rights, rights)
+        })?;
+        new_handle_value.write(new_value)?;
+        Ok(())
+    }
+
+    /// Replace a handle with a new one with access rights `rights`.
+    ///
+    /// The original handle is always invalidated.
+    pub fn sys_handle_replace(
+        &self,
+        handle_value: HandleValue,
+        rights: u32,
+        mut out: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        info!(
+            "handle.replace: handle={:#x?}, rights={:#x?}",
+            handle_value, rights
+        );
+        let proc = self.thread.proc();
+        let new_value = proc.replace_handle_operating_rights(handle_value, |handle_rights| {
+            let rights = Rights::from_bits(rights).ok_or(ZxError::INVALID_ARGS)?;
+            reduce_rights(handle_rights, rights)
+        })?;
+        out.write(new_value)?;
+        Ok(())
+    }
+}
+
+/// Get the rights of a new handle derived from one with `handle_rights`.
+///
+/// The new `rights` must be a subset of `handle_rights`, unless it is exactly `SAME_RIGHTS`.
+/// Return `INVALID_ARGS` otherwise, e.g. for `SAME_RIGHTS` combined with other rights.
+pub(super) fn reduce_rights(handle_rights: Rights, rights: Rights) -> ZxResult<Rights> {
+    if rights == Rights::SAME_RIGHTS {
+        Ok(handle_rights)
+    } else if handle_rights.contains(rights) {
+        Ok(rights)
+    } else {
+        Err(ZxError::INVALID_ARGS)
+    }
+}
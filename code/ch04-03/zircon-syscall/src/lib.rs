@@ -12,14 +12,22 @@ use {
     core::convert::TryFrom,
     kernel_hal::user::*,
     zircon_object::object::*,
-    zircon_object::task::{CurrentThread, ThreadFn},
+    zircon_object::task::{CurrentThread, ThreadFn, ThreadState},
 };
 
 mod channel;
 mod consts;
 mod debuglog;
+mod futex;
+mod handle;
+mod object;
+mod port;
+mod signal;
+mod task;
+mod time;
 
 use consts::SyscallType as Sys;
+use time::Deadline;
 
 pub struct Syscall<'a> {
     pub thread: &'a CurrentThread,
@@ -41,8 +49,33 @@ impl Syscall<'_> {
             "{}|{} {:?} => args={:x?}",
             proc_name, thread_name, sys_type, args
         );
+        let ret = loop {
+            let ret = self.dispatch(&sys_type, args).await;
+            match (ret, &sys_type) {
+                // the vDSO finishes an interrupted call by `channel_call_finish`
+                (Err(ZxError::INTERNAL_INTR_RETRY), Sys::CHANNEL_CALL_NORETRY)
+                | (Err(ZxError::INTERNAL_INTR_RETRY), Sys::CHANNEL_CALL_FINISH) => break ret,
+                // other interrupted syscalls are restarted after the thread is resumed
+                (Err(ZxError::INTERNAL_INTR_RETRY), _) => {
+                    let cx = self.thread.wait_for_run().await;
+                    self.thread.end_running(cx);
+                    if self.thread.state() == ThreadState::Dying {
+                        break Err(ZxError::INTERNAL_INTR_KILLED);
+                    }
+                }
+                _ => break ret,
+            }
+        };
+        info!("{}|{} {:?} <= {:?}", proc_name, thread_name, sys_type, ret);
+        match ret {
+            Ok(_) => 0,
+            Err(err) => err as isize,
+        }
+    }
+
+    async fn dispatch(&mut self, sys_type: &Sys, args: [usize; 8]) -> ZxResult {
         let [a0, a1, a2, a3, a4, a5, a6, a7] = args;
-        let ret = match sys_type {
+        match sys_type {
             Sys::CHANNEL_CREATE => self.sys_channel_create(a0 as _, a1.into(), a2.into()),
             Sys::CHANNEL_READ => self.sys_channel_read(
                 a0 as _,
@@ -53,22 +86,124 @@ impl Syscall<'_> {
                 a5 as _,
                 a6.into(),
                 a7.into(),
+                false,
+            ),
+            Sys::CHANNEL_READ_ETC => self.sys_channel_read(
+                a0 as _,
+                a1 as _,
+                a2.into(),
+                a3 as _,
+                a4 as _,
+                a5 as _,
+                a6.into(),
+                a7.into(),
+                true,
             ),
             Sys::CHANNEL_WRITE => {
                 self.sys_channel_write(a0 as _, a1 as _, a2.into(), a3 as _, a4.into(), a5 as _)
             }
+            Sys::CHANNEL_WRITE_ETC => {
+                self.sys_channel_write_etc(a0 as _, a1 as _, a2.into(), a3 as _, a4.into(), a5 as _)
+            }
+            Sys::CHANNEL_CALL_NORETRY => {
+                self.sys_channel_call_noretry(
+                    a0 as _,
+                    a1 as _,
+                    a2.into(),
+                    a3.into(),
+                    a4.into(),
+                    a5.into(),
+                )
+                .await
+            }
+            Sys::CHANNEL_CALL_FINISH => {
+                self.sys_channel_call_finish(a0.into(), a1.into(), a2.into(), a3.into())
+                    .await
+            }
             Sys::DEBUGLOG_CREATE => self.sys_debuglog_create(a0 as _, a1 as _, a2.into()),
             Sys::DEBUGLOG_WRITE => self.sys_debuglog_write(a0 as _, a1 as _, a2.into(), a3 as _),
             Sys::DEBUGLOG_READ => self.sys_debuglog_read(a0 as _, a1 as _, a2.into(), a3 as _),
+            Sys::HANDLE_CLOSE => self.sys_handle_close(a0 as _),
+            Sys::HANDLE_CLOSE_MANY => self.sys_handle_close_many(a0.into(), a1 as _),
+            Sys::HANDLE_DUPLICATE => self.sys_handle_duplicate(a0 as _, a1 as _, a2.into()),
+            Sys::HANDLE_REPLACE => self.sys_handle_replace(a0 as _, a1 as _, a2.into()),
+            Sys::OBJECT_WAIT_ONE => {
+                self.sys_object_wait_one(a0 as _, a1 as _, a2.into(), a3.into())
+                    .await
+            }
+            Sys::OBJECT_WAIT_MANY => {
+                self.sys_object_wait_many(a0.into(), a1 as _, a2.into())
+                    .await
+            }
+            Sys::OBJECT_WAIT_ASYNC => {
+                self.sys_object_wait_async(a0 as _, a1 as _, a2 as _, a3 as _, a4 as _)
+            }
+            Sys::FUTEX_WAIT => {
+                self.sys_futex_wait(a0.into(), a1 as _, a2 as _, a3.into())
+                    .await
+            }
+            Sys::FUTEX_WAKE => self.sys_futex_wake(a0.into(), a1 as _),
+            Sys::FUTEX_REQUEUE => {
+                self.sys_futex_requeue(a0.into(), a1 as _, a2 as _, a3.into(), a4 as _, a5 as _)
+            }
+            Sys::FUTEX_WAKE_SINGLE_OWNER => self.sys_futex_wake_single_owner(a0.into()),
+            Sys::FUTEX_REQUEUE_SINGLE_OWNER => {
+                self.sys_futex_requeue_single_owner(a0.into(), a1 as _, a2.into(), a3 as _, a4 as _)
+            }
+            Sys::FUTEX_GET_OWNER => self.sys_futex_get_owner(a0.into(), a1.into()),
+            Sys::OBJECT_SIGNAL => self.sys_object_signal(a0 as _, a1 as _, a2 as _),
+            Sys::OBJECT_SIGNAL_PEER => self.sys_object_signal_peer(a0 as _, a1 as _, a2 as _),
+            Sys::OBJECT_GET_PROPERTY => {
+                self.sys_object_get_property(a0 as _, a1 as _, a2 as _, a3 as _)
+            }
+            Sys::OBJECT_SET_PROPERTY => {
+                self.sys_object_set_property(a0 as _, a1 as _, a2 as _, a3 as _)
+            }
+            Sys::OBJECT_GET_CHILD => {
+                self.sys_object_get_child(a0 as _, a1 as _, a2 as _, a3.into())
+            }
+            Sys::OBJECT_GET_INFO => {
+                self.sys_object_get_info(a0 as _, a1 as _, a2 as _, a3 as _, a4.into(), a5.into())
+            }
+            Sys::EVENT_CREATE => self.sys_event_create(a0 as _, a1.into()),
+            Sys::EVENTPAIR_CREATE => self.sys_eventpair_create(a0 as _, a1.into(), a2.into()),
+            Sys::TIMER_CREATE => self.sys_timer_create(a0 as _, a1 as _, a2.into()),
+            Sys::TIMER_SET => self.sys_timer_set(a0 as _, a1.into(), a2 as _),
+            Sys::TIMER_CANCEL => self.sys_timer_cancel(a0 as _),
+            Sys::PORT_CREATE => self.sys_port_create(a0 as _, a1.into()),
+            Sys::PORT_QUEUE => self.sys_port_queue(a0 as _, a1.into()),
+            Sys::PORT_WAIT => self.sys_port_wait(a0 as _, a1.into(), a2.into()).await,
+            Sys::PROCESS_CREATE => {
+                self.sys_process_create(a0 as _, a1.into(), a2 as _, a3 as _, a4.into(), a5.into())
+            }
+            Sys::PROCESS_START => {
+                self.sys_process_start(a0 as _, a1 as _, a2 as _, a3 as _, a4 as _, a5 as _)
+            }
+            Sys::PROCESS_EXIT => self.sys_process_exit(a0 as _),
+            Sys::JOB_CREATE => self.sys_job_create(a0 as _, a1 as _, a2.into()),
+            Sys::JOB_SET_POLICY => {
+                self.sys_job_set_policy(a0 as _, a1 as _, a2 as _, a3 as _, a4 as _)
+            }
+            Sys::JOB_SET_CRITICAL => self.sys_job_set_critical(a0 as _, a1 as _, a2 as _),
+            Sys::THREAD_CREATE => {
+                self.sys_thread_create(a0 as _, a1.into(), a2 as _, a3 as _, a4.into())
+            }
+            Sys::THREAD_START => self.sys_thread_start(a0 as _, a1 as _, a2 as _, a3 as _, a4 as _),
+            Sys::THREAD_EXIT => self.sys_thread_exit(),
+            Sys::THREAD_READ_STATE => {
+                self.sys_thread_read_state(a0 as _, a1 as _, a2.into(), a3 as _)
+            }
+            Sys::THREAD_WRITE_STATE => {
+                self.sys_thread_write_state(a0 as _, a1 as _, a2.into(), a3 as _)
+            }
+            Sys::TASK_SUSPEND | Sys::TASK_SUSPEND_TOKEN => {
+                self.sys_task_suspend_token(a0 as _, a1.into())
+            }
+            Sys::TASK_KILL => self.sys_task_kill(a0 as _),
             _ => {
                 error!("syscall unimplemented: {:?}", sys_type);
                 Err(ZxError::NOT_SUPPORTED)
             }
-        };
-        info!("{}|{} {:?} <= {:?}", proc_name, thread_name, sys_type, ret);
-        match ret {
-            Ok(_) => 0,
-            Err(err) => err as isize,
         }
     }
 }
@@ -0,0 +1,334 @@
+use {
+    super::{handle::reduce_rights, *},
+    alloc::vec::Vec,
+    zircon_object::{ipc::Port, task::ThreadState, vm::*},
+};
+
+impl Syscall<'_> {
+    /// Wait for signals on an object.
+    pub async fn sys_object_wait_one(
+        &self,
+        handle_value: HandleValue,
+        signals: u32,
+        deadline: Deadline,
+        mut observed: UserOutPtr<Signal>,
+    ) -> ZxResult {
+        info!(
+            "object.wait_one: handle={:#x?}, signals={:#x?}, deadline={:#x?}, observed={:#x?}",
+            handle_value, signals, deadline, observed
+        );
+        let signals = Signal::from_bits(signals).ok_or(ZxError::INVALID_ARGS)?;
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle_value, Rights::WAIT)?;
+        let future = object.wait_signal(signals);
+        let signal = self
+            .thread
+            .blocking_run(future, ThreadState::BlockedWaitOne, deadline.into())
+            .await
+            .or_else(|err| {
+                if err == ZxError::TIMED_OUT {
+                    observed.write_if_not_null(object.signal())?;
+                }
+                Err(err)
+            })?;
+        observed.write_if_not_null(signal)?;
+        Ok(())
+    }
+
+    /// Wait for signals on multiple objects.
+    pub async fn sys_object_wait_many(
+        &self,
+        mut user_items: UserInOutPtr<UserWaitItem>,
+        count: u32,
+        deadline: Deadline,
+    ) -> ZxResult {
+        info!(
+            "object.wait_many: items={:#x?}, count={:#x?}, deadline={:#x?}",
+            user_items, count, deadline
+        );
+        if count > MAX_WAIT_HANDLES {
+            return Err(ZxError::OUT_OF_RANGE);
+        }
+        let proc = self.thread.proc();
+        let mut items = user_items.read_array(count as usize)?;
+        let mut waiters = Vec::with_capacity(count as usize);
+        for item in items.iter() {
+            let object = proc.get_dyn_object_with_rights(item.handle, Rights::WAIT)?;
+            waiters.push((object, item.wait_for));
+        }
+        let future = wait_signal_many(&waiters);
+        let res = self
+            .thread
+            .blocking_run(future, ThreadState::BlockedWaitMany, deadline.into())
+            .await;
+        let (signals, ret) = match res {
+            Ok(signals) => (signals, Ok(())),
+            Err(ZxError::TIMED_OUT) => {
+                let signals = waiters.iter().map(|(obj, _)| obj.signal()).collect();
+                (signals, Err(ZxError::TIMED_OUT))
+            }
+            Err(err) => return Err(err),
+        };
+        for (item, signal) in items.iter_mut().zip(signals) {
+            item.observed = signal;
+        }
+        user_items.write_array(&items)?;
+        ret
+    }
+
+    /// Subscribe for signals on an object.
+    pub fn sys_object_wait_async(
+        &self,
+        handle_value: HandleValue,
+        port_handle_value: HandleValue,
+        key: u64,
+        signals: u32,
+        options: u32,
+    ) -> ZxResult {
+        info!(
+            "object.wait_async: handle={:#x}, port={:#x}, key={:#x}, signals={:#x?}, options={:#x}",
+            handle_value, port_handle_value, key, signals, options
+        );
+        let signals = Signal::from_bits(signals).ok_or(ZxError::INVALID_ARGS)?;
+        let repeating = match options {
+            WAIT_ASYNC_ONCE => false,
+            WAIT_ASYNC_REPEATING => true,
+            _ => return Err(ZxError::INVALID_ARGS),
+        };
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle_value, Rights::WAIT)?;
+        let port = proc.get_object_with_rights::<Port>(port_handle_value, Rights::WRITE)?;
+        object.send_signal_to_port_async(signals, &port, key, repeating)
+    }
+
+    /// Signal an object.
+    pub fn sys_object_signal(
+        &self,
+        handle_value: HandleValue,
+        clear_mask: u32,
+        set_mask: u32,
+    ) -> ZxResult {
+        info!(
+            "object.signal: handle={:#x}, clear_mask={:#x}, set_mask={:#x}",
+            handle_value, clear_mask, set_mask
+        );
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle_value, Rights::SIGNAL)?;
+        let allowed_signals = object.allowed_signals();
+        let clear = Signal::verify_user_signal(allowed_signals, clear_mask)?;
+        let set = Signal::verify_user_signal(allowed_signals, set_mask)?;
+        object.signal_change(clear, set);
+        Ok(())
+    }
+
+    /// Signal an object's peer.
+    pub fn sys_object_signal_peer(
+        &self,
+        handle_value: HandleValue,
+        clear_mask: u32,
+        set_mask: u32,
+    ) -> ZxResult {
+        info!(
+            "object.signal_peer: handle={:#x}, clear_mask={:#x}, set_mask={:#x}",
+            handle_value, clear_mask, set_mask
+        );
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle_value, Rights::SIGNAL_PEER)?;
+        let peer = object.peer()?;
+        let allowed_signals = peer.allowed_signals();
+        let clear = Signal::verify_user_signal(allowed_signals, clear_mask)?;
+        let set = Signal::verify_user_signal(allowed_signals, set_mask)?;
+        peer.signal_change(clear, set);
+        Ok(())
+    }
+
+    /// Query information about an object.
+    ///
+    /// Single-record topics fail with `BUFFER_TOO_SMALL` if the buffer can not hold one record.
+    /// For array topics, as many records as fit are written to the buffer,
+    /// `actual` is set to the number written and `avail` to the number available.
+    pub fn sys_object_get_info(
+        &self,
+        handle_value: HandleValue,
+        topic: u32,
+        buffer: usize,
+        buffer_size: usize,
+        mut actual: UserOutPtr<usize>,
+        mut avail: UserOutPtr<usize>,
+    ) -> ZxResult {
+        info!(
+            "object.get_info: handle={:#x?}, topic={:#x?}, buffer=({:#x?}; {:#x?})",
+            handle_value, topic, buffer, buffer_size
+        );
+        let topic = Topic::try_from(topic).map_err(|_| ZxError::INVALID_ARGS)?;
+        let proc = self.thread.proc();
+        let info = match topic {
+            Topic::HandleValid => {
+                proc.get_handle(handle_value)?;
+                return Ok(());
+            }
+            Topic::HandleBasic => {
+                let handle = proc.get_handle(handle_value)?;
+                ObjectInfo::single(handle.get_handle_basic_info())
+            }
+            Topic::HandleCount => {
+                let object = proc.get_dyn_object_with_rights(handle_value, Rights::INSPECT)?;
+                ObjectInfo::single(HandleCountInfo {
+                    handle_count: object.handle_count(),
+                })
+            }
+            Topic::Vmo => {
+                let handle = proc.get_handle(handle_value)?;
+                let vmo = handle
+                    .object
+                    .downcast_arc::<VmObject>()
+                    .map_err(|_| ZxError::WRONG_TYPE)?;
+                let mut vmo_info = vmo.get_info();
+                vmo_info.flags |= VmoInfoFlags::VIA_HANDLE;
+                vmo_info.rights = handle.rights;
+                ObjectInfo::single(vmo_info)
+            }
+            Topic::ProcessThreads | Topic::JobChildren | Topic::JobProcesses => proc
+                .get_dyn_object_with_rights(handle_value, Rights::ENUMERATE)?
+                .get_info(topic)?,
+            _ => proc
+                .get_dyn_object_with_rights(handle_value, Rights::INSPECT)?
+                .get_info(topic)?,
+        };
+        let count = if info.is_array() {
+            buffer_size / info.record_size()
+        } else if buffer_size < info.record_size() {
+            return Err(ZxError::BUFFER_TOO_SMALL);
+        } else {
+            1
+        };
+        let count = count.min(info.count());
+        UserOutPtr::<u8>::from(buffer).write_array(info.as_bytes(count))?;
+        actual.write_if_not_null(count)?;
+        avail.write_if_not_null(info.count())?;
+        Ok(())
+    }
+
+    /// Find the child of an object by its koid.
+    ///
+    /// The new handle has the same rights as `handle_value` if `rights` is `SAME_RIGHTS`,
+    /// otherwise `rights` must be a subset of them.
+    pub fn sys_object_get_child(
+        &self,
+        handle_value: HandleValue,
+        koid: KoID,
+        rights: u32,
+        mut out: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        info!(
+            "object.get_child: handle={:#x?}, koid={:#x?}, rights={:#x?}",
+            handle_value, koid, rights
+        );
+        let rights = Rights::from_bits(rights).ok_or(ZxError::INVALID_ARGS)?;
+        let proc = self.thread.proc();
+        let parent = proc.get_handle(handle_value)?;
+        if !parent.rights.contains(Rights::ENUMERATE) {
+            return Err(ZxError::ACCESS_DENIED);
+        }
+        let rights = reduce_rights(parent.rights, rights).map_err(|_| ZxError::ACCESS_DENIED)?;
+        let child = parent.object.get_child(koid)?;
+        out.write(proc.add_handle(Handle::new(child, rights))?)?;
+        Ok(())
+    }
+
+    /// Ask for various properties of various kernel objects.
+    pub fn sys_object_get_property(
+        &self,
+        handle_value: HandleValue,
+        property: u32,
+        buffer: usize,
+        buffer_size: usize,
+    ) -> ZxResult {
+        info!(
+            "object.get_property: handle={:#x?}, property={:#x?}, buffer=({:#x?}; {:#x?})",
+            handle_value, property, buffer, buffer_size
+        );
+        let property = Property::try_from(property).map_err(|_| ZxError::INVALID_ARGS)?;
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle_value, Rights::GET_PROPERTY)?;
+        if buffer_size < property.value_size() {
+            return Err(ZxError::BUFFER_TOO_SMALL);
+        }
+        match property {
+            Property::Name => {
+                let mut name = [0u8; MAX_NAME_LEN];
+                let s = object.name();
+                let len = s.len().min(MAX_NAME_LEN - 1);
+                name[..len].copy_from_slice(&s.as_bytes()[..len]);
+                UserOutPtr::<[u8; MAX_NAME_LEN]>::from(buffer).write(name)?;
+            }
+            Property::RegisterFs | Property::RegisterGs => return Err(ZxError::INVALID_ARGS),
+            Property::ExceptionState => {
+                let value = object.get_property(property)?;
+                UserOutPtr::<u32>::from(buffer).write(value as u32)?;
+            }
+            _ => {
+                let value = object.get_property(property)?;
+                UserOutPtr::<usize>::from(buffer).write(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set various properties of various kernel objects.
+    pub fn sys_object_set_property(
+        &self,
+        handle_value: HandleValue,
+        property: u32,
+        buffer: usize,
+        buffer_size: usize,
+    ) -> ZxResult {
+        info!(
+            "object.set_property: handle={:#x?}, property={:#x?}, buffer=({:#x?}; {:#x?})",
+            handle_value, property, buffer, buffer_size
+        );
+        let property = Property::try_from(property).map_err(|_| ZxError::INVALID_ARGS)?;
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle_value, Rights::SET_PROPERTY)?;
+        match property {
+            Property::Name => {
+                // names longer than the limit are truncated
+                let len = buffer_size.min(MAX_NAME_LEN - 1);
+                let name = UserInPtr::<u8>::from(buffer).read_string(len)?;
+                object.set_name(name.trim_end_matches('\0'));
+                return Ok(());
+            }
+            Property::ProcessVdsoBaseAddress => return Err(ZxError::INVALID_ARGS),
+            // only the calling thread can set its own thread-local registers
+            Property::RegisterFs | Property::RegisterGs
+                if object.obj_type() == ObjectType::Thread && object.id() != self.thread.id() =>
+            {
+                return Err(ZxError::ACCESS_DENIED);
+            }
+            _ => {}
+        }
+        if buffer_size < property.value_size() {
+            return Err(ZxError::BUFFER_TOO_SMALL);
+        }
+        let value = match property {
+            Property::ExceptionState => UserInPtr::<u32>::from(buffer).read()? as usize,
+            _ => UserInPtr::<usize>::from(buffer).read()?,
+        };
+        object.set_property(property, value)
+    }
+}
+
+const WAIT_ASYNC_ONCE: u32 = 0;
+const WAIT_ASYNC_REPEATING: u32 = 1;
+
+/// The maximum number of items in `zx_object_wait_many`.
+const MAX_WAIT_HANDLES: u32 = 64;
+
+/// `zx_wait_item_t`
+#[repr(C)]
+#[derive(Debug)]
+pub struct UserWaitItem {
+    handle: HandleValue,
+    wait_for: Signal,
+    observed: Signal,
+}
@@ -0,0 +1,77 @@
+use {
+    super::*,
+    alloc::boxed::Box,
+    zircon_object::{ipc::*, task::*},
+};
+
+impl Syscall<'_> {
+    /// Create an IO port.
+    pub fn sys_port_create(&self, options: u32, mut out: UserOutPtr<HandleValue>) -> ZxResult {
+        info!("port.create: options={:#x}", options);
+        if options != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let proc = self.thread.proc();
+        proc.check_policy(PolicyCondition::NewPort)?;
+        let port = Port::new();
+        let handle = proc.add_handle(Handle::new(port, Rights::DEFAULT_PORT))?;
+        out.write(handle)?;
+        Ok(())
+    }
+
+    /// Queue a user packet to an IO port.
+    pub fn sys_port_queue(
+        &self,
+        handle_value: HandleValue,
+        packet_in: UserInPtr<UserPortPacket>,
+    ) -> ZxResult {
+        let packet = packet_in.read()?;
+        info!(
+            "port.queue: handle={:#x}, packet={:?}",
+            handle_value, packet
+        );
+        let proc = self.thread.proc();
+        let port = proc.get_object_with_rights::<Port>(handle_value, Rights::WRITE)?;
+        port.push(PortPacketRepr {
+            key: packet.key,
+            status: ZxError::OK,
+            data: PayloadRepr::User(packet.user),
+        })
+    }
+
+    /// Wait for a packet arrival in a port.
+    pub async fn sys_port_wait(
+        &self,
+        handle_value: HandleValue,
+        deadline: Deadline,
+        mut packet_out: UserOutPtr<PortPacket>,
+    ) -> ZxResult {
+        info!(
+            "port.wait: handle={:#x}, deadline={:?}",
+            handle_value, deadline
+        );
+        let proc = self.thread.proc();
+        let port = proc.get_object_with_rights::<Port>(handle_value, Rights::READ)?;
+        let future = port.wait();
+        let packet = self
+            .thread
+            .blocking_run(Box::pin(future), ThreadState::BlockedPort, deadline.into())
+            .await?;
+        debug!("port.wait: packet={:?}", packet);
+        packet_out.write(packet.into())?;
+        Ok(())
+    }
+}
+
+/// `zx_port_packet_t` queued by userspace.
+///
+/// The type and status are read as plain integers,
+/// since the kernel always delivers it as a user packet with status OK.
+#[repr(C)]
+#[derive(Debug)]
+pub struct UserPortPacket {
+    key: u64,
+    type_: u32,
+    status: i32,
+    user: PacketUser,
+}
@@ -0,0 +1,38 @@
+use {super::*, zircon_object::signal::*, zircon_object::task::PolicyCondition};
+
+impl Syscall<'_> {
+    /// Create an event.
+    pub fn sys_event_create(&self, options: u32, mut out: UserOutPtr<HandleValue>) -> ZxResult {
+        info!("event.create: options={:#x?}", options);
+        if options != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let proc = self.thread.proc();
+        proc.check_policy(PolicyCondition::NewEvent)?;
+        let event = Event::new();
+        let handle = proc.add_handle(Handle::new(event, Rights::DEFAULT_EVENT))?;
+        out.write(handle)?;
+        Ok(())
+    }
+
+    /// Create an event pair.
+    pub fn sys_eventpair_create(
+        &self,
+        options: u32,
+        mut out0: UserOutPtr<HandleValue>,
+        mut out1: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        info!("eventpair.create: options={:#x?}", options);
+        if options != 0 {
+            return Err(ZxError::NOT_SUPPORTED);
+        }
+        let proc = self.thread.proc();
+        proc.check_policy(PolicyCondition::NewEventPair)?;
+        let (event0, event1) = EventPair::create();
+        let handle0 = proc.add_handle(Handle::new(event0, Rights::DEFAULT_EVENTPAIR))?;
+        let handle1 = proc.add_handle(Handle::new(event1, Rights::DEFAULT_EVENTPAIR))?;
+        out0.write(handle0)?;
+        out1.write(handle1)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,382 @@
+use {
+    super::*,
+    alloc::{sync::Arc, vec, vec::Vec},
+    core::time::Duration,
+    zircon_object::{signal::Slack, task::*},
+};
+
+/// The maximum size of the buffer for thread state.
+const MAX_THREAD_STATE_SIZE: usize = 0x1000;
+
+const JOB_POL_RELATIVE: u32 = 0;
+const JOB_POL_ABSOLUTE: u32 = 1;
+const JOB_POL_BASIC: u32 = 0;
+const JOB_POL_TIMER_SLACK: u32 = 1;
+const JOB_POL_BASIC_V2: u32 = 0x0100_0000;
+
+/// Kill the job only if the critical process exits with a nonzero retcode.
+const JOB_CRITICAL_PROCESS_RETCODE_NONZERO: u32 = 1;
+
+/// A basic policy in user memory, i.e. `zx_policy_basic_v1_t`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct BasicPolicyInfo {
+    condition: u32,
+    policy: u32,
+}
+
+/// A basic policy with override flags in user memory, i.e. `zx_policy_basic_v2_t`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct BasicPolicyV2Info {
+    condition: u32,
+    action: u32,
+    flags: u32,
+}
+
+/// A timer slack policy in user memory, i.e. `zx_policy_timer_slack_t`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct TimerSlackInfo {
+    min_slack: i64,
+    default_mode: u32,
+    padding: u32,
+}
+
+impl Syscall<'_> {
+    /// Create a new process in the job.
+    ///
+    /// Return the handles of the process and its root VMAR.
+    pub fn sys_process_create(
+        &self,
+        job_handle: HandleValue,
+        name: UserInPtr<u8>,
+        name_size: usize,
+        options: u32,
+        mut proc_out: UserOutPtr<HandleValue>,
+        mut vmar_out: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        let name = name.read_string(name_size)?;
+        info!(
+            "proc.create: job={:#x?}, name={:?}, options={:#x?}",
+            job_handle, name, options,
+        );
+        if options != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let proc = self.thread.proc();
+        proc.check_policy(PolicyCondition::NewProcess)?;
+        let job = proc.get_object_with_rights::<Job>(job_handle, Rights::MANAGE_PROCESS)?;
+        let new_proc = Process::create(&job, &name)?;
+        let new_vmar = new_proc.vmar();
+        let vmar_rights = Rights::DEFAULT_VMAR | Rights::READ | Rights::WRITE | Rights::EXECUTE;
+        let values = proc.add_handles(vec![
+            Handle::new(new_proc, Rights::DEFAULT_PROCESS),
+            Handle::new(new_vmar, vmar_rights),
+        ])?;
+        proc_out.write(values[0])?;
+        vmar_out.write(values[1])?;
+        Ok(())
+    }
+
+    /// Start the first thread of a process.
+    ///
+    /// The ownership of `arg1_handle` is transferred to the new process,
+    /// and its value in the new process is passed as the first argument.
+    pub fn sys_process_start(
+        &self,
+        proc_handle: HandleValue,
+        thread_handle: HandleValue,
+        entry: usize,
+        stack: usize,
+        arg1_handle: HandleValue,
+        arg2: usize,
+    ) -> ZxResult {
+        info!(
+            "proc.start: proc={:#x?}, thread={:#x?}, entry={:#x?}, stack={:#x?}, arg1={:#x?}, arg2={:#x?}",
+            proc_handle, thread_handle, entry, stack, arg1_handle, arg2
+        );
+        let proc = self.thread.proc();
+        let process = proc.get_object_with_rights::<Process>(proc_handle, Rights::WRITE)?;
+        let thread = proc.get_object_with_rights::<Thread>(thread_handle, Rights::WRITE)?;
+        if !Arc::ptr_eq(thread.proc(), &process) {
+            return Err(ZxError::ACCESS_DENIED);
+        }
+        let arg1 = if arg1_handle != INVALID_HANDLE {
+            let arg1 = proc.remove_handle(arg1_handle)?;
+            if !arg1.rights.contains(Rights::TRANSFER) {
+                return Err(ZxError::ACCESS_DENIED);
+            }
+            Some(arg1)
+        } else {
+            None
+        };
+        process.start(&thread, entry, stack, arg1, arg2, self.thread_fn)
+    }
+
+    /// Exit the current process with `retcode`.
+    ///
+    /// All threads of the process are killed, including the current one,
+    /// so this syscall never returns to the user.
+    pub fn sys_process_exit(&self, retcode: i64) -> ZxResult {
+        info!("proc.exit: retcode={:?}", retcode);
+        let proc = self.thread.proc();
+        proc.exit(retcode);
+        Err(ZxError::STOP)
+    }
+
+    /// Create a new thread in the process.
+    pub fn sys_thread_create(
+        &self,
+        proc_handle: HandleValue,
+        name: UserInPtr<u8>,
+        name_size: usize,
+        options: u32,
+        mut thread_out: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        let name = name.read_string(name_size)?;
+        info!(
+            "thread.create: proc={:#x?}, name={:?}, options={:#x?}",
+            proc_handle, name, options,
+        );
+        if options != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let proc = self.thread.proc();
+        let process = proc.get_object_with_rights::<Process>(proc_handle, Rights::MANAGE_THREAD)?;
+        let thread = Thread::create(&process, &name)?;
+        let handle = proc.add_handle(Handle::new(thread, Rights::DEFAULT_THREAD))?;
+        thread_out.write(handle)?;
+        Ok(())
+    }
+
+    /// Start a thread of a running process.
+    ///
+    /// The first thread of a process must be started by `process_start`.
+    pub fn sys_thread_start(
+        &self,
+        handle_value: HandleValue,
+        entry: usize,
+        stack: usize,
+        arg1: usize,
+        arg2: usize,
+    ) -> ZxResult {
+        info!(
+            "thread.start: handle={:#x?}, entry={:#x?}, stack={:#x?}, arg1={:#x?}, arg2={:#x?}",
+            handle_value, entry, stack, arg1, arg2
+        );
+        let proc = self.thread.proc();
+        let thread = proc.get_object_with_rights::<Thread>(handle_value, Rights::MANAGE_THREAD)?;
+        if thread.proc().status() != Status::Running {
+            return Err(ZxError::BAD_STATE);
+        }
+        thread.start(entry, stack, arg1, arg2, self.thread_fn)
+    }
+
+    /// Exit the current thread.
+    ///
+    /// This syscall never returns to the user.
+    pub fn sys_thread_exit(&self) -> ZxResult {
+        info!("thread.exit:");
+        self.thread.exit();
+        Err(ZxError::STOP)
+    }
+
+    /// Read one aspect of the state of a suspended thread.
+    pub fn sys_thread_read_state(
+        &self,
+        handle_value: HandleValue,
+        kind: u32,
+        mut buffer: UserOutPtr<u8>,
+        buffer_size: usize,
+    ) -> ZxResult {
+        info!(
+            "thread.read_state: handle={:#x?}, kind={:#x?}, buffer_size={:#x?}",
+            handle_value, kind, buffer_size
+        );
+        let kind = ThreadStateKind::try_from(kind).map_err(|_| ZxError::INVALID_ARGS)?;
+        if buffer_size > MAX_THREAD_STATE_SIZE {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let proc = self.thread.proc();
+        let thread = proc.get_object_with_rights::<Thread>(handle_value, Rights::READ)?;
+        let mut buf = vec![0u8; buffer_size];
+        let len = thread.read_state(kind, &mut buf)?;
+        buffer.write_array(&buf[..len])?;
+        Ok(())
+    }
+
+    /// Write one aspect of the state of a suspended thread.
+    pub fn sys_thread_write_state(
+        &self,
+        handle_value: HandleValue,
+        kind: u32,
+        buffer: UserInPtr<u8>,
+        buffer_size: usize,
+    ) -> ZxResult {
+        info!(
+            "thread.write_state: handle={:#x?}, kind={:#x?}, buffer_size={:#x?}",
+            handle_value, kind, buffer_size
+        );
+        let kind = ThreadStateKind::try_from(kind).map_err(|_| ZxError::INVALID_ARGS)?;
+        if buffer_size > MAX_THREAD_STATE_SIZE {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let proc = self.thread.proc();
+        let thread = proc.get_object_with_rights::<Thread>(handle_value, Rights::WRITE)?;
+        let buf = buffer.read_array(buffer_size)?;
+        thread.write_state(kind, &buf)
+    }
+
+    /// Create a new child job of the parent job.
+    pub fn sys_job_create(
+        &self,
+        parent: HandleValue,
+        options: u32,
+        mut out: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        info!("job.create: parent={:#x?}, options={:#x?}", parent, options);
+        if options != 0 {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let proc = self.thread.proc();
+        let parent_job = proc.get_object_with_rights::<Job>(parent, Rights::MANAGE_JOB)?;
+        let child = parent_job.create_child()?;
+        let handle = proc.add_handle(Handle::new(child, Rights::DEFAULT_JOB))?;
+        out.write(handle)?;
+        Ok(())
+    }
+
+    /// Set policies of an empty job.
+    pub fn sys_job_set_policy(
+        &self,
+        handle: HandleValue,
+        options: u32,
+        topic: u32,
+        policy: usize,
+        count: u32,
+    ) -> ZxResult {
+        info!(
+            "job.set_policy: handle={:#x?}, options={:#x?}, topic={:#x?}, policy={:#x?}, count={:#x?}",
+            handle, options, topic, policy, count
+        );
+        let proc = self.thread.proc();
+        let job = proc.get_object_with_rights::<Job>(handle, Rights::SET_POLICY)?;
+        match topic {
+            JOB_POL_BASIC | JOB_POL_BASIC_V2 => {
+                let options = match options {
+                    JOB_POL_RELATIVE => SetPolicyOptions::Relative,
+                    JOB_POL_ABSOLUTE => SetPolicyOptions::Absolute,
+                    _ => return Err(ZxError::INVALID_ARGS),
+                };
+                if count == 0 || count as usize > POLICY_CONDITION_COUNT {
+                    return Err(ZxError::INVALID_ARGS);
+                }
+                let infos: Vec<BasicPolicyV2Info> = if topic == JOB_POL_BASIC {
+                    UserInPtr::<BasicPolicyInfo>::from(policy)
+                        .read_array(count as usize)?
+                        .into_iter()
+                        .map(|info| BasicPolicyV2Info {
+                            condition: info.condition,
+                            action: info.policy,
+                            flags: POLICY_OVERRIDE_DENY,
+                        })
+                        .collect()
+                } else {
+                    UserInPtr::<BasicPolicyV2Info>::from(policy).read_array(count as usize)?
+                };
+                let mut policies = Vec::with_capacity(infos.len());
+                for info in infos {
+                    policies.push(BasicPolicyV2 {
+                        condition: PolicyCondition::try_from(info.condition)
+                            .map_err(|_| ZxError::INVALID_ARGS)?,
+                        action: PolicyAction::try_from(info.action)
+                            .map_err(|_| ZxError::INVALID_ARGS)?,
+                        override_allowed: match info.flags {
+                            POLICY_OVERRIDE_ALLOW => true,
+                            POLICY_OVERRIDE_DENY => false,
+                            _ => return Err(ZxError::INVALID_ARGS),
+                        },
+                    });
+                }
+                job.set_policy_basic_v2(options, &policies)
+            }
+            JOB_POL_TIMER_SLACK => {
+                if options != JOB_POL_RELATIVE || count != 1 {
+                    return Err(ZxError::INVALID_ARGS);
+                }
+                let info = UserInPtr::<TimerSlackInfo>::from(policy).read()?;
+                if info.min_slack < 0 {
+                    return Err(ZxError::INVALID_ARGS);
+                }
+                job.set_policy_timer_slack(TimerSlack {
+                    amount: Duration::from_nanos(info.min_slack as u64),
+                    mode: Slack::try_from(info.default_mode).map_err(|_| ZxError::INVALID_ARGS)?,
+                })
+            }
+            _ => Err(ZxError::INVALID_ARGS),
+        }
+    }
+
+    /// Make a process critical to a job.
+    ///
+    /// When the process terminates, the job is killed as if `task_kill` was called on it.
+    pub fn sys_job_set_critical(
+        &self,
+        job_handle: HandleValue,
+        options: u32,
+        proc_handle: HandleValue,
+    ) -> ZxResult {
+        info!(
+            "job.set_critical: job={:#x?}, options={:#x?}, proc={:#x?}",
+            job_handle, options, proc_handle
+        );
+        let retcode_nonzero = match options {
+            0 => false,
+            JOB_CRITICAL_PROCESS_RETCODE_NONZERO => true,
+            _ => return Err(ZxError::INVALID_ARGS),
+        };
+        let proc = self.thread.proc();
+        let job = proc.get_object_with_rights::<Job>(job_handle, Rights::DESTROY)?;
+        let process = proc.get_object_with_rights::<Process>(proc_handle, Rights::WAIT)?;
+        process.set_critical_job(&job, retcode_nonzero)
+    }
+
+    /// Suspend a thread or a process, until the token in `token_out` is closed.
+    pub fn sys_task_suspend_token(
+        &self,
+        handle: HandleValue,
+        mut token_out: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        info!("task.suspend_token: handle={:#x?}", handle);
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle, Rights::WRITE)?;
+        let token = SuspendToken::create(object)?;
+        let handle = proc.add_handle(Handle::new(token, Rights::TRANSFER | Rights::INSPECT))?;
+        token_out.write(handle)?;
+        Ok(())
+    }
+
+    /// Kill a job, a process or a thread.
+    ///
+    /// This syscall does not return if the current thread is killed.
+    pub fn sys_task_kill(&self, handle: HandleValue) -> ZxResult {
+        info!("task.kill: handle={:#x?}", handle);
+        let proc = self.thread.proc();
+        let object = proc.get_dyn_object_with_rights(handle, Rights::DESTROY)?;
+        let task: Arc<dyn Task> = if let Ok(job) = object.clone().downcast_arc::<Job>() {
+            job
+        } else if let Ok(process) = object.clone().downcast_arc::<Process>() {
+            process
+        } else if let Ok(thread) = object.downcast_arc::<Thread>() {
+            thread
+        } else {
+            return Err(ZxError::WRONG_TYPE);
+        };
+        task.kill();
+        if self.thread.state() == ThreadState::Dying {
+            return Err(ZxError::STOP);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use {
+    super::*,
+    core::convert::TryFrom,
+    core::time::Duration,
+    zircon_object::{signal::*, task::PolicyCondition},
+};
+
+impl Syscall<'_> {
+    /// Create a timer, an object that can signal when a specified point in time has been reached.
+    pub fn sys_timer_create(
+        &self,
+        options: u32,
+        clock_id: u32,
+        mut out: UserOutPtr<HandleValue>,
+    ) -> ZxResult {
+        info!(
+            "timer.create: options={:#x?}, clock_id={:#x?}",
+            options, clock_id
+        );
+        if clock_id != ZX_CLOCK_MONOTONIC {
+            return Err(ZxError::INVALID_ARGS);
+        }
+        let slack = Slack::try_from(options).map_err(|_| ZxError::INVALID_ARGS)?;
+        let proc = self.thread.proc();
+        proc.check_policy(PolicyCondition::NewTimer)?;
+        // Deviation from Zircon: `ZX_TIMER_SLACK_CENTER` is 0, the same as the
+        // options of a timer not requesting any mode, so a timer asking for
+        // `Center` explicitly also takes the mode of the timer slack policy.
+        // Whatever the mode is, the timer fires exactly at its deadline,
+        // which is allowed by every mode.
+        let slack = match slack {
+            Slack::Center => proc.timer_slack().mode,
+            _ => slack,
+        };
+        let timer = Timer::create(slack);
+        let handle = proc.add_handle(Handle::new(timer, Rights::DEFAULT_TIMER))?;
+        out.write(handle)?;
+        Ok(())
+    }
+
+    /// Start a one-shot timer that will fire when `deadline` passes.
+    pub fn sys_timer_set(&self, handle: HandleValue, deadline: Deadline, slack: i64) -> ZxResult {
+        info!(
+            "timer.set: handle={:#x?}, deadline={:?}, slack={:#x?}",
+            handle, deadline, slack
+        );
+        if slack < 0 {
+            return Err(ZxError::OUT_OF_RANGE);
+        }
+        let proc = self.thread.proc();
+        let timer = proc.get_object_with_rights::<Timer>(handle, Rights::WRITE)?;
+        // the slack is at least the minimum of the timer slack policy
+        let slack = Duration::from_nanos(slack as u64).max(proc.timer_slack().amount);
+        timer.set(deadline.into(), slack);
+        Ok(())
+    }
+
+    /// Cancel a pending timer that was started with `timer_set`.
+    pub fn sys_timer_cancel(&self, handle: HandleValue) -> ZxResult {
+        info!("timer.cancel: handle={:#x?}", handle);
+        let proc = self.thread.proc();
+        let timer = proc.get_object_with_rights::<Timer>(handle, Rights::WRITE)?;
+        timer.cancel();
+        Ok(())
+    }
+}
+
+/// The system monotonic clock.
+const ZX_CLOCK_MONOTONIC: u32 = 0;
+
+/// An absolute deadline of the monotonic clock, in nanoseconds.
+///
+/// Unlike Zircon, the timer slack policy is not applied to the deadlines
+/// of waits: they always expire exactly at the deadline.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Deadline(i64);
+
+impl From<usize> for Deadline {
+    fn from(x: usize) -> Self {
+        Deadline(x as i64)
+    }
+}
+
+impl From<Deadline> for Duration {
+    fn from(deadline: Deadline) -> Self {
+        Duration::from_nanos(deadline.0.max(0) as u64)
+    }
+}